@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = snss::parse_lenient(data);
+    let Ok(snss) = snss::parse(data) else {
+        return;
+    };
+    for tab in snss.tabs() {
+        let _ = tab.page_state();
+    }
+    let _ = snss.windows();
+    let _ = snss.to_bytes();
+});
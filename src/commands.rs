@@ -0,0 +1,69 @@
+//! Raw command ids, to match on [Command::id](crate::Command::id)
+//!
+//! Ids of the session service are named after the `kCommand*` constants of
+//! `session_service_commands.cc`, see also [CommandId](crate::CommandId). The tab restore service
+//! numbers its commands differently, see [tabs].
+
+pub const SET_TAB_WINDOW: u8 = 0;
+/// Obsolete, superseded by [SET_WINDOW_BOUNDS3]
+pub const SET_WINDOW_BOUNDS: u8 = 1;
+pub const SET_TAB_INDEX_IN_WINDOW: u8 = 2;
+/// Obsolete, superseded by [TAB_NAVIGATION_PATH_PRUNED]
+pub const TAB_NAVIGATION_PATH_PRUNED_FROM_BACK: u8 = 5;
+pub const UPDATE_TAB_NAVIGATION: u8 = 6;
+pub const SET_SELECTED_NAVIGATION_INDEX: u8 = 7;
+pub const SET_SELECTED_TAB_IN_INDEX: u8 = 8;
+pub const SET_WINDOW_TYPE: u8 = 9;
+/// Obsolete, superseded by [SET_WINDOW_BOUNDS3]
+pub const SET_WINDOW_BOUNDS2: u8 = 10;
+/// Obsolete, superseded by [TAB_NAVIGATION_PATH_PRUNED]
+pub const TAB_NAVIGATION_PATH_PRUNED_FROM_FRONT: u8 = 11;
+pub const SET_PINNED_STATE: u8 = 12;
+pub const SET_EXTENSION_APP_ID: u8 = 13;
+pub const SET_WINDOW_BOUNDS3: u8 = 14;
+pub const SET_WINDOW_APP_NAME: u8 = 15;
+pub const TAB_CLOSED: u8 = 16;
+pub const WINDOW_CLOSED: u8 = 17;
+/// Obsolete, superseded by [SET_TAB_USER_AGENT_OVERRIDE2]
+pub const SET_TAB_USER_AGENT_OVERRIDE: u8 = 18;
+pub const SESSION_STORAGE_ASSOCIATED: u8 = 19;
+pub const SET_ACTIVE_WINDOW: u8 = 20;
+pub const LAST_ACTIVE_TIME: u8 = 21;
+/// Obsolete, superseded by [SET_WINDOW_WORKSPACE2]
+pub const SET_WINDOW_WORKSPACE: u8 = 22;
+pub const SET_WINDOW_WORKSPACE2: u8 = 23;
+pub const TAB_NAVIGATION_PATH_PRUNED: u8 = 24;
+pub const SET_TAB_GROUP: u8 = 25;
+/// Obsolete, superseded by [SET_TAB_GROUP_METADATA2]
+pub const SET_TAB_GROUP_METADATA: u8 = 26;
+pub const SET_TAB_GROUP_METADATA2: u8 = 27;
+pub const SET_TAB_GUID: u8 = 28;
+pub const SET_TAB_USER_AGENT_OVERRIDE2: u8 = 29;
+pub const SET_TAB_DATA: u8 = 30;
+pub const SET_WINDOW_USER_TITLE: u8 = 31;
+pub const SET_WINDOW_VISIBLE_ON_ALL_WORKSPACES: u8 = 32;
+pub const ADD_TAB_EXTRA_DATA: u8 = 33;
+pub const ADD_WINDOW_EXTRA_DATA: u8 = 34;
+pub const SET_PLATFORM_SESSION_ID: u8 = 35;
+
+/// Marker written after the initial state, see
+/// [SNSS::effective_commands](crate::SNSS::effective_commands)
+pub const INITIAL_STATE_MARKER: u8 = 255;
+
+/// Ids of the tab restore service (`tab_restore_service_impl.cc`), written in Tabs files
+pub mod tabs {
+    pub const UPDATE_TAB_NAVIGATION: u8 = 1;
+    pub const RESTORED_ENTRY: u8 = 2;
+    /// Obsolete, superseded by [WINDOW]
+    pub const WINDOW_DEPRECATED: u8 = 3;
+    pub const SELECTED_NAVIGATION_IN_TAB: u8 = 4;
+    pub const PINNED_STATE: u8 = 5;
+    pub const SET_EXTENSION_APP_ID: u8 = 6;
+    pub const SET_WINDOW_APP_NAME: u8 = 7;
+    /// Obsolete, superseded by [SET_TAB_USER_AGENT_OVERRIDE2]
+    pub const SET_TAB_USER_AGENT_OVERRIDE: u8 = 8;
+    pub const WINDOW: u8 = 9;
+    pub const SET_TAB_GROUP_DATA: u8 = 10;
+    pub const SET_TAB_USER_AGENT_OVERRIDE2: u8 = 11;
+    pub const SET_WINDOW_USER_TITLE: u8 = 12;
+}
@@ -4,24 +4,59 @@
 //! ```no_run
 //! let data = std::fs::read("Session")?;
 //! let snss = snss::parse(&data)?;
-//! for command in snss.commands {
-//!     if let snss::Content::Tab(tab) = command.content {
-//!         println!("Tab #{}: [{}]({})", tab.id, tab.title, tab.url);
-//!     }
+//! for tab in snss.tabs() {
+//!     println!("Tab #{}: {tab}", tab.id);
 //! }
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
+//!
+//! Strings of the parsed file borrow from `data`, use `parse_owned` for a result that outlives it.
+//!
+//! # Features
+//! - `std` (default): reading from files and readers, and converting timestamps to `SystemTime`.
+//!   Without it the crate is `no_std` and only needs `alloc`.
+//! - `serde`: (de)serialization of the parsed data.
+//! - `json`: `SNSS::to_json` and `SNSS::to_sessions_json`, for tools like `jq`.
+//! - `bitflags`: qualifiers of a page transition as a set of flags.
+//! - `time`: converting timestamps to `time::OffsetDateTime`, also without `std`.
+//! - `gzip`: parsing gzip-compressed files with `parse_maybe_compressed`.
+//! - `url`: urls of tabs without their tracking parameters, with `Tab::clean_url`.
+//! - `csv`: `SNSS::write_tabs_csv`, for spreadsheets.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
-use std::fmt::Display;
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt::Display, ops::Range};
+#[cfg(feature = "std")]
+use std::{
+    ffi::OsStr,
+    io::Read,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use winnow::{
     Bytes, Parser,
-    binary::{le_i32, le_u8, le_u16, le_u32, length_and_then},
-    combinator::{seq, trace},
-    error::StrContext,
+    binary::{le_i32, le_i64, le_u8, le_u16, le_u32, length_take},
+    combinator::{alt, empty, eof, preceded, repeat_till, seq, trace},
+    error::{ContextError, FromExternalError, StrContext, StrContextValue},
     token::{rest, take},
 };
 
+pub mod commands;
+mod page_state;
+mod write;
+pub use page_state::{PageState, ViewState};
+
 // Thanks for the following sources:
 // - https://digitalinvestigation.wordpress.com/tag/snss
 // - https://github.com/phacoxcll/SNSS_Reader
@@ -29,97 +64,1859 @@ use winnow::{
 
 #[derive(Debug)]
 pub struct Error {
+    kind: ErrorKind,
     message: String,
     offset: usize,
+    /// Labels of the fields being parsed, from the outermost to the innermost
+    context: Vec<&'static str>,
+    command_index: Option<usize>,
+}
+impl core::error::Error for Error {}
+
+/// Cause of an [Error]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ErrorKind {
+    /// The data doesn't start with `SNSS`
+    BadMagic,
+    /// The version isn't one of [SUPPORTED_VERSIONS]
+    UnsupportedVersion(i32),
+    /// The length prefix of a command runs past the end of the data
+    TruncatedCommand,
+    /// A string isn't valid UTF-8
+    InvalidUtf8 { field: &'static str },
+    /// A string isn't valid UTF-16
+    InvalidUtf16 { field: &'static str },
+    /// A field runs past the end of its command
+    UnexpectedEof,
+    /// The data exceeds a limit of [ParseOptions], or a command is too large for [SNSS::to_bytes]
+    LimitExceeded,
+    /// Reading the data failed, or it is otherwise malformed
+    Other,
+}
+
+/// Context of the strings failing to decode as UTF-8
+const INVALID_UTF8: StrContext = StrContext::Expected(StrContextValue::Description("UTF-8"));
+/// Context of the strings failing to decode as UTF-16
+const INVALID_UTF16: StrContext = StrContext::Expected(StrContextValue::Description("UTF-16"));
+
+impl Error {
+    fn from_context(offset: usize, err: ContextError) -> Self {
+        let mut context: Vec<_> = err
+            .context()
+            .filter_map(|context| match context {
+                StrContext::Label(label) => Some(*label),
+                _ => None,
+            })
+            .collect();
+        context.reverse();
+
+        let field = context.last().copied().unwrap_or_default();
+        let kind = if err.context().any(|c| *c == INVALID_UTF8) {
+            ErrorKind::InvalidUtf8 { field }
+        } else if err.context().any(|c| *c == INVALID_UTF16) {
+            ErrorKind::InvalidUtf16 { field }
+        } else if context.first() == Some(&"magic") {
+            ErrorKind::BadMagic
+        } else if context.first() == Some(&"command") {
+            ErrorKind::TruncatedCommand
+        } else if err.context().any(|c| matches!(c, StrContext::Expected(_))) {
+            ErrorKind::Other
+        } else {
+            ErrorKind::UnexpectedEof
+        };
+
+        if kind == ErrorKind::BadMagic {
+            return Error {
+                kind,
+                message: "not an SNSS file: expected magic 'SNSS'".to_string(),
+                offset: 0,
+                context,
+                command_index: None,
+            };
+        }
+
+        #[cfg(feature = "std")]
+        let message = match err.cause() {
+            Some(cause) => cause.to_string(),
+            None => err.to_string(),
+        };
+        #[cfg(not(feature = "std"))]
+        let message = err.to_string();
+
+        Error {
+            kind,
+            message,
+            offset,
+            context,
+            command_index: None,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Position in the parsed data where the error occurred
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Labels of the fields being parsed when the error occurred, from the outermost to the
+    /// innermost (eg. `["url"]`)
+    pub fn context(&self) -> &[&'static str] {
+        &self.context
+    }
+
+    /// Index of the command that failed to parse, [None] if the error isn't about a command
+    pub fn command_index(&self) -> Option<usize> {
+        self.command_index
+    }
 }
-impl std::error::Error for Error {}
 
 impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "error at offset {}: {}", self.offset, self.message)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.context.is_empty() {
+            writeln!(f, "error at offset {}: {}", self.offset, self.message)
+        } else {
+            writeln!(
+                f,
+                "error at offset {} in field '{}': {}",
+                self.offset,
+                self.context.join("."),
+                self.message
+            )
+        }
     }
 }
 
-pub fn parse(data: &[u8]) -> Result<SNSS, Error> {
-    parse_snss.parse(Bytes::new(data)).map_err(|err| Error {
-        offset: err.offset(),
-        message: err.into_inner().to_string(),
+/// Parse a SNSS file
+///
+/// UTF-8 strings (urls, app names, ...) borrow from `data` instead of being copied, use
+/// [SNSS::into_owned] to keep the result around longer than `data`.
+///
+/// Whether `data` is a Session or a Tabs file is guessed from its command ids (see [SNSS::kind]),
+/// use [parse_with] if it is known.
+///
+/// In every [SUPPORTED_VERSIONS], a command is framed by a `u16` size with no escape to a wider
+/// one: Chrome drops the commands that don't fit instead of splitting them, so a size of
+/// `0xFFFF` is an ordinary size.
+pub fn parse(data: &[u8]) -> Result<SNSS<'_>, Error> {
+    parse_with_options(data, ParseOptions::default())
+}
+
+/// Like [parse], decoding the commands as used by `kind` of file
+///
+/// [FileKind::Unknown] decodes both id 1 and 6 as [Content::Tab].
+pub fn parse_with(data: &[u8], kind: FileKind) -> Result<SNSS<'_>, Error> {
+    let options = ParseOptions {
+        kind: Some(kind),
+        ..ParseOptions::default()
+    };
+    parse_with_options(data, options)
+}
+
+/// Options of [parse_with_options]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ParseOptions {
+    /// Kind of file to decode the commands of, guessed like [parse] if [None]
+    pub kind: Option<FileKind>,
+    /// Replace invalid UTF-8 in the urls and lone surrogates in the titles with U+FFFD instead
+    /// of failing
+    pub lossy: bool,
+    /// Fail on files with more commands than this
+    pub max_commands: Option<usize>,
+    /// Fail on commands whose payload (after the id) is longer than this
+    pub max_payload_len: Option<usize>,
+}
+
+/// Like [parse], as configured by `options`
+pub fn parse_with_options(data: &[u8], options: ParseOptions) -> Result<SNSS<'_>, Error> {
+    let mut reader = SnssReader::with_options(data, options)?;
+    let commands = reader.by_ref().collect::<Result<_, _>>()?;
+    Ok(SNSS {
+        version: reader.version(),
+        commands,
     })
 }
 
+/// Like [parse], but copying the strings so that the result doesn't borrow from `data`
+pub fn parse_owned(data: &[u8]) -> Result<SNSS<'static>, Error> {
+    parse(data).map(SNSS::into_owned)
+}
+
+/// Decode the commands of `data` lazily, like [parse] without collecting them
+///
+/// A bad header is yielded as the only item, use [SnssReader] to read the version.
+pub fn commands_iter(data: &[u8]) -> impl Iterator<Item = Result<Command<'_>, Error>> {
+    let (reader, header) = match SnssReader::new(data) {
+        Ok(reader) => (Some(reader), None),
+        Err(err) => (None, Some(Err(err))),
+    };
+    header.into_iter().chain(reader.into_iter().flatten())
+}
+
+/// Versions of SNSS files that can be parsed
+///
+/// Versions 2 and 4 are their encrypted counterparts.
+pub const SUPPORTED_VERSIONS: [i32; 2] = [
+    1, // kFileVersion1
+    3, // kFileVersionWithMarker
+];
+
+/// Read only the version and the number of commands of `data`, without decoding the commands
+///
+/// The version isn't checked against [SUPPORTED_VERSIONS].
+pub fn probe(data: &[u8]) -> Result<(i32, usize), Error> {
+    let commands = repeat_till(
+        0..,
+        length_take(le_u16).context(StrContext::Label("command")),
+        eof,
+    )
+    .map(|(count, _): (usize, _)| count);
+    (parse_header, commands)
+        .parse(Bytes::new(data))
+        .map_err(|err| Error::from_context(err.offset(), err.into_inner()))
+}
+
+/// Like [parse], but a malformed command is skipped instead of failing the whole file
+///
+/// Parsing resumes at the next command thanks to the length prefix of the malformed one, and the
+/// error is collected instead. Only a bad header (reported with a `version` of 0) or a length
+/// prefix running past the end of `data` stop early.
+pub fn parse_lenient(data: &[u8]) -> (SNSS<'_>, Vec<Error>) {
+    let options = ParseOptions {
+        kind: Some(FileKind::detect(data)),
+        ..ParseOptions::default()
+    };
+    let mut input = Bytes::new(data);
+    let offset = |input: &Bytes| data.len() - input.len();
+
+    let version = match parse_header.parse_next(&mut input) {
+        Ok(version) => version,
+        Err(err) => {
+            let snss = SNSS {
+                version: 0,
+                commands: Vec::new(),
+            };
+            return (snss, vec![Error::from_context(offset(input), err)]);
+        }
+    };
+    if let Err(err) = check_version(version) {
+        let snss = SNSS {
+            version,
+            commands: Vec::new(),
+        };
+        return (snss, vec![err]);
+    }
+
+    let mut commands = Vec::new();
+    let mut errors = Vec::new();
+    for index in 0.. {
+        if input.is_empty() {
+            break;
+        }
+        let error = |offset, err| Error {
+            command_index: Some(index),
+            ..Error::from_context(offset, err)
+        };
+        // after the length prefix
+        let start = offset(input) + 2;
+        let frame = match length_take(le_u16)
+            .context(StrContext::Label("command"))
+            .parse_next(&mut input)
+        {
+            Ok(frame) => frame,
+            Err(err) => {
+                errors.push(error(offset(input), err));
+                break;
+            }
+        };
+        match parse_frame(options, start, frame) {
+            Ok(command) => commands.push(command),
+            Err(err) => errors.push(Error {
+                command_index: Some(index),
+                ..err
+            }),
+        }
+    }
+
+    (SNSS { version, commands }, errors)
+}
+
+/// Like [parse], but an incomplete last command, eg. of a file Chrome was writing when it crashed,
+/// is returned as trailing bytes instead of failing the whole file
+///
+/// The trailing bytes are empty if every command is complete.
+pub fn parse_partial(data: &[u8]) -> Result<(SNSS<'_>, &[u8]), Error> {
+    let mut reader = SnssReader::new(data)?;
+    let mut commands = Vec::new();
+    loop {
+        let rest = reader.input;
+        match reader.next() {
+            Some(Ok(command)) => commands.push(command),
+            Some(Err(err)) if err.kind == ErrorKind::TruncatedCommand => {
+                let version = reader.version;
+                return Ok((SNSS { version, commands }, rest));
+            }
+            Some(Err(err)) => return Err(err),
+            None => {
+                let version = reader.version;
+                return Ok((SNSS { version, commands }, &[]));
+            }
+        }
+    }
+}
+
+/// Read `reader` to the end then [parse] it
+#[cfg(feature = "std")]
+pub fn parse_from_reader<R: Read>(mut reader: R) -> Result<SNSS<'static>, Error> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).map_err(|err| Error {
+        kind: ErrorKind::Other,
+        offset: 0,
+        message: err.to_string(),
+        context: Vec::new(),
+        command_index: None,
+    })?;
+    parse(&data).map(SNSS::into_owned)
+}
+
+/// [parse] `data`, decompressing it first if it is gzip-compressed
+#[cfg(feature = "gzip")]
+pub fn parse_maybe_compressed(data: &[u8]) -> Result<SNSS<'_>, Error> {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        parse_from_reader(flate2::read::MultiGzDecoder::new(data))
+    } else {
+        parse(data)
+    }
+}
+
+/// Decode the commands of `reader` as they are read, without reading it whole first
+///
+/// The kind of file can't be guessed before its end, so it is decoded as a [FileKind::Session]
+/// file, see [parse_reader_with] for Tabs files or [parse_from_reader] to guess it.
+#[cfg(feature = "std")]
+pub fn parse_reader<R: Read>(reader: R) -> Result<SNSS<'static>, Error> {
+    parse_reader_with(reader, FileKind::Session)
+}
+
+/// Like [parse_reader], decoding the commands like [parse_with]
+#[cfg(feature = "std")]
+pub fn parse_reader_with<R: Read>(mut reader: R, kind: FileKind) -> Result<SNSS<'static>, Error> {
+    let io_error = |offset, err: std::io::Error| Error {
+        kind: ErrorKind::Other,
+        offset,
+        message: err.to_string(),
+        context: Vec::new(),
+        command_index: None,
+    };
+
+    let mut header = [0; 8];
+    let len = read_up_to(&mut reader, &mut header).map_err(|err| io_error(0, err))?;
+    let mut input = Bytes::new(&header[..len]);
+    let version = parse_header
+        .parse_next(&mut input)
+        .map_err(|err| Error::from_context(len - input.len(), err))?;
+    check_version(version)?;
+
+    let options = ParseOptions {
+        kind: Some(kind),
+        ..ParseOptions::default()
+    };
+    let mut commands = Vec::new();
+    let mut offset = header.len();
+    let mut buf = Vec::new();
+    for index in 0.. {
+        let io_error = |err| Error {
+            command_index: Some(index),
+            ..io_error(offset, err)
+        };
+        buf.resize(2, 0);
+        let mut len = read_up_to(&mut reader, &mut buf).map_err(io_error)?;
+        if len == 0 {
+            break;
+        }
+        if len == buf.len() {
+            buf.resize(2 + u16::from_le_bytes([buf[0], buf[1]]) as usize, 0);
+            len += read_up_to(&mut reader, &mut buf[2..]).map_err(io_error)?;
+        }
+        buf.truncate(len);
+
+        let mut input = Bytes::new(&buf);
+        // after the length prefix
+        let start = offset + 2;
+        let command = length_take(le_u16)
+            .context(StrContext::Label("command"))
+            .parse_next(&mut input)
+            .map_err(|err| Error::from_context(offset + len - input.len(), err))
+            .and_then(|frame| parse_frame(options, start, frame))
+            .map_err(|err| Error {
+                command_index: Some(index),
+                ..err
+            })?;
+        commands.push(command.into_owned());
+        offset += len;
+    }
+    Ok(SNSS { version, commands })
+}
+
+/// Fill `buf` from `reader`, returning how much of it was filled before the end of `reader`
+#[cfg(feature = "std")]
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(read) => len += read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(len)
+}
+
+/// Read the file at `path` then [parse] it
+///
+/// The kind of file is taken from its name if it follows Chrome's naming (see
+/// [FileKind::from_file_name]), and guessed from its content otherwise.
+#[cfg(feature = "std")]
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<SNSS<'static>, Error> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|err| Error {
+        kind: ErrorKind::Other,
+        offset: 0,
+        message: format!("failed to read {}: {err}", path.display()),
+        context: Vec::new(),
+        command_index: None,
+    })?;
+    let kind = match path.file_name().map(FileKind::from_file_name) {
+        Some(FileKind::Unknown) | None => FileKind::detect(&data),
+        Some(kind) => kind,
+    };
+    parse_with(&data, kind).map(SNSS::into_owned)
+}
+
+/// [parse_file] every file starting with the SNSS magic in `dir` (eg. Chrome's `Sessions`
+/// directory), sorted by path
+///
+/// The error of each file is kept with its path, and if `dir` can't be listed its error is
+/// returned as the only result.
+#[cfg(feature = "std")]
+pub fn parse_dir<P: AsRef<Path>>(dir: P) -> Vec<(PathBuf, Result<SNSS<'static>, Error>)> {
+    let dir = dir.as_ref();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            let err = Error {
+                kind: ErrorKind::Other,
+                offset: 0,
+                message: format!("failed to read {}: {err}", dir.display()),
+                context: Vec::new(),
+                command_index: None,
+            };
+            return vec![(dir.to_path_buf(), Err(err))];
+        }
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            let mut magic = [0; 4];
+            std::fs::File::open(path)
+                .and_then(|mut file| file.read_exact(&mut magic))
+                .is_ok_and(|()| magic == *b"SNSS")
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let snss = parse_file(&path);
+            (path, snss)
+        })
+        .collect()
+}
+
+/// Iterator decoding the commands of a SNSS file one at a time
+///
+/// Iteration stops after the first error.
 #[derive(Debug)]
-pub struct SNSS {
+pub struct SnssReader<'a> {
+    data: &'a [u8],
+    input: &'a Bytes,
+    version: i32,
+    /// With the kind of file resolved
+    options: ParseOptions,
+    /// Index of the next command
+    index: usize,
+}
+
+impl<'a> SnssReader<'a> {
+    /// Check the header of `data`, guessing its kind like [parse]
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        SnssReader::with_options(data, ParseOptions::default())
+    }
+
+    /// Check the header of `data`, decoding its commands like [parse_with]
+    pub fn with_kind(data: &'a [u8], kind: FileKind) -> Result<Self, Error> {
+        let options = ParseOptions {
+            kind: Some(kind),
+            ..ParseOptions::default()
+        };
+        SnssReader::with_options(data, options)
+    }
+
+    /// Check the header of `data`, decoding its commands like [parse_with_options]
+    pub fn with_options(data: &'a [u8], options: ParseOptions) -> Result<Self, Error> {
+        let kind = options.kind.unwrap_or_else(|| FileKind::detect(data));
+        let mut input = Bytes::new(data);
+        let version = parse_header
+            .parse_next(&mut input)
+            .map_err(|err| Error::from_context(data.len() - input.len(), err))?;
+        check_version(version)?;
+        Ok(SnssReader {
+            data,
+            input,
+            version,
+            options: ParseOptions {
+                kind: Some(kind),
+                ..options
+            },
+            index: 0,
+        })
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Why the next command exceeds the limits of the options, if it does
+    fn exceeded_limit(&self) -> Option<String> {
+        if let Some(max) = self.options.max_commands
+            && self.index >= max
+        {
+            return Some(format!("more than {max} commands"));
+        }
+        let len = self
+            .input
+            .get(..2)
+            .map_or(0, |len| u16::from_le_bytes([len[0], len[1]]));
+        // the length counts the id
+        let payload_len = usize::from(len).saturating_sub(1);
+        match self.options.max_payload_len {
+            Some(max) if payload_len > max => Some(format!(
+                "command payload of {payload_len} bytes exceeds {max} bytes"
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Iterator for SnssReader<'a> {
+    type Item = Result<Command<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        if let Some(message) = self.exceeded_limit() {
+            let offset = self.data.len() - self.input.len();
+            self.input = Bytes::new(&[]);
+            return Some(Err(Error {
+                kind: ErrorKind::LimitExceeded,
+                message,
+                offset,
+                context: vec!["command"],
+                command_index: Some(self.index),
+            }));
+        }
+        // after the length prefix
+        let start = self.data.len() - self.input.len() + 2;
+        let command = length_take(le_u16)
+            .context(StrContext::Label("command"))
+            .parse_next(&mut self.input)
+            .map_err(|err| Error::from_context(self.data.len() - self.input.len(), err))
+            .and_then(|frame| parse_frame(self.options, start, frame))
+            .map_err(|err| {
+                self.input = Bytes::new(&[]);
+                Error {
+                    command_index: Some(self.index),
+                    ..err
+                }
+            });
+        self.index += 1;
+        Some(command)
+    }
+}
+
+#[derive(Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SNSS<'a> {
     pub version: i32,
-    pub commands: Vec<Command>,
+    pub commands: Vec<Command<'a>>,
+}
+
+impl<'a> SNSS<'a> {
+    /// File without commands, eg. to [push](SNSS::push) built ones and [encode](SNSS::to_bytes) it
+    pub fn new(version: i32) -> Self {
+        SNSS {
+            version,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Append `command`, see [Command::new]
+    pub fn push(&mut self, command: Command<'a>) -> &mut Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// Iterate over the commands holding state, skipping the bookkeeping ones
+    ///
+    /// The only bookkeeping command is the marker (id 255, `kInitialStateMarkerCommandId`) that
+    /// Chrome writes after the initial state of a [VersionInfo::WithMarker] file, it has no
+    /// payload and only delimits the commands appended later.
+    pub fn effective_commands(&self) -> impl Iterator<Item = &Command<'a>> {
+        self.commands
+            .iter()
+            .filter(|command| command.id != commands::INITIAL_STATE_MARKER)
+    }
+
+    /// Iterate over every [Content::Tab] in the file
+    pub fn tabs(&self) -> impl Iterator<Item = &Tab<'a>> {
+        self.commands
+            .iter()
+            .filter_map(|command| match &command.content {
+                Content::Tab(tab) => Some(tab),
+                _ => None,
+            })
+    }
+
+    /// Consuming version of [SNSS::tabs]
+    pub fn into_tabs(self) -> impl Iterator<Item = Tab<'a>> {
+        self.commands
+            .into_iter()
+            .filter_map(|command| match command.content {
+                Content::Tab(tab) => Some(tab),
+                _ => None,
+            })
+    }
+
+    /// Ids of the tabs left pinned by the [Content::PinnedState] commands, in ascending order
+    pub fn pinned_tabs(&self) -> Vec<i32> {
+        let mut pinned = BTreeMap::new();
+        for command in &self.commands {
+            if let Content::PinnedState {
+                tab_id,
+                pinned: state,
+            } = command.content
+            {
+                pinned.insert(tab_id, state);
+            }
+        }
+        pinned
+            .into_iter()
+            .filter_map(|(tab_id, pinned)| pinned.then_some(tab_id))
+            .collect()
+    }
+
+    /// Ids of the tabs of each group, according to the latest [Content::TabGroup] of every tab
+    pub fn tab_groups(&self) -> BTreeMap<[u8; 16], Vec<i32>> {
+        let mut tabs = BTreeMap::new();
+        for command in &self.commands {
+            if let Content::TabGroup { tab_id, group } = command.content {
+                tabs.insert(tab_id, group);
+            }
+        }
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (tab_id, group) in tabs {
+            if let Some(group) = group {
+                groups.entry(group).or_default().push(tab_id);
+            }
+        }
+        groups
+    }
+
+    /// Navigation entries of the tab `tab_id`, ordered by [Tab::index]
+    ///
+    /// An entry updated several times is represented by its latest [Content::Tab].
+    pub fn navigation_history(&self, tab_id: i32) -> Vec<&Tab<'a>> {
+        let mut entries = BTreeMap::new();
+        for tab in self.tabs().filter(|tab| tab.id == tab_id) {
+            entries.insert(tab.index, tab);
+        }
+        entries.into_values().collect()
+    }
+
+    /// Reconstruct the windows of the session with their tabs
+    ///
+    /// Tabs are assigned to windows by [Content::TabWindow] and ordered by
+    /// [Content::TabIndexInWindow]. Each tab is represented by its selected navigation entry (see
+    /// [Content::SelectedNavigationIndex]), or its latest one. Closed tabs and windows are left out.
+    ///
+    /// Windows are in the order of their first command. A tab moved to another window belongs to
+    /// the window of its latest [Content::TabWindow], and a tab without any is left out.
+    pub fn windows(&self) -> Vec<Window<'_, 'a>> {
+        struct TabState<'s, 'a> {
+            /// Order of appearance
+            seen: usize,
+            window_id: Option<i32>,
+            index: Option<i32>,
+            selected_navigation: Option<i32>,
+            navigations: Vec<&'s Tab<'a>>,
+        }
+
+        fn tab<'m, 's, 'a>(
+            tabs: &'m mut BTreeMap<i32, TabState<'s, 'a>>,
+            id: i32,
+        ) -> &'m mut TabState<'s, 'a> {
+            let seen = tabs.len();
+            tabs.entry(id).or_insert_with(|| TabState {
+                seen,
+                window_id: None,
+                index: None,
+                selected_navigation: None,
+                navigations: Vec::new(),
+            })
+        }
+
+        fn window<'m, 's, 'a>(
+            windows: &'m mut Vec<Window<'s, 'a>>,
+            id: i32,
+        ) -> &'m mut Window<'s, 'a> {
+            match windows.iter().position(|window| window.id == id) {
+                Some(position) => &mut windows[position],
+                None => {
+                    windows.push(Window {
+                        id,
+                        tabs: Vec::new(),
+                        selected_index: None,
+                    });
+                    windows.last_mut().unwrap()
+                }
+            }
+        }
+
+        let mut windows = Vec::new();
+        let mut tabs = BTreeMap::new();
+        for command in &self.commands {
+            match &command.content {
+                Content::Tab(navigation) => {
+                    tab(&mut tabs, navigation.id).navigations.push(navigation)
+                }
+                Content::TabWindow { window_id, tab_id } => {
+                    window(&mut windows, *window_id);
+                    tab(&mut tabs, *tab_id).window_id = Some(*window_id);
+                }
+                Content::TabIndexInWindow { tab_id, index } => {
+                    tab(&mut tabs, *tab_id).index = Some(*index)
+                }
+                Content::SelectedNavigationIndex { tab_id, index } => {
+                    tab(&mut tabs, *tab_id).selected_navigation = Some(*index)
+                }
+                Content::SelectedTabInIndex { window_id, index } => {
+                    window(&mut windows, *window_id).selected_index = Some(*index)
+                }
+                Content::TabClosed { tab_id, .. } => tab(&mut tabs, *tab_id).window_id = None,
+                Content::WindowClosed { window_id, .. } => {
+                    windows.retain(|window| window.id != *window_id)
+                }
+                _ => {}
+            }
+        }
+
+        let mut tabs: Vec<_> = tabs.into_values().collect();
+        tabs.sort_by_key(|tab| (tab.index, tab.seen));
+        for tab in tabs {
+            let navigation = tab
+                .navigations
+                .iter()
+                .rfind(|navigation| Some(navigation.index) == tab.selected_navigation)
+                .or(tab.navigations.last());
+            let window = tab
+                .window_id
+                .and_then(|id| windows.iter_mut().find(|window| window.id == id));
+            if let (Some(navigation), Some(window)) = (navigation, window) {
+                window.tabs.push(navigation);
+            }
+        }
+        windows
+    }
+
+    /// Guess whether this is a Session or a Tabs file from the command ids it contains
+    ///
+    /// Ids 1, 3 and 4 are only written by the tab restore service while 0 and 14 to 35 are only
+    /// written by the session service. A file without either falls back to [FileKind::Session] if it
+    /// has id 6, which both services use but the tab restore service always pairs with id 1.
+    pub fn kind(&self) -> FileKind {
+        FileKind::from_ids(self.commands.iter().map(|command| command.id))
+    }
+
+    /// Like [SNSS::kind], but [None] when the commands don't tell the kind apart, for callers that
+    /// only have the bytes and no file name to fall back on
+    pub fn detect_kind(&self) -> Option<FileKind> {
+        match self.kind() {
+            FileKind::Unknown => None,
+            kind => Some(kind),
+        }
+    }
+
+    /// Known format of [SNSS::version]
+    pub fn version_info(&self) -> VersionInfo {
+        VersionInfo::from_version(self.version)
+    }
+
+    /// JSON of the file, with the transition of each tab flattened into its `kind`, its qualifiers
+    /// as booleans and its `raw` value
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).expect("SNSS has no non-string keys");
+        let commands = json["commands"].as_array_mut().into_iter().flatten();
+        for command in commands {
+            let Some(transition) = command.pointer_mut("/content/Tab/transition") else {
+                continue;
+            };
+            let mut flat = serde_json::Map::new();
+            flat.insert("raw".into(), transition["value"].take());
+            flat.insert("kind".into(), transition["kind"].take());
+            if let serde_json::Value::Object(qualifiers) = transition["qualifiers"].take() {
+                flat.extend(qualifiers);
+            }
+            *transition = flat.into();
+        }
+        json
+    }
+
+    /// JSON of the [windows](SNSS::windows) of the session, laid out like `about:sessions`
+    ///
+    /// Each tab has its `id`, `url`, `title`, `pinned` state and its transition as a string (see
+    /// [PageTransition]'s [Display]).
+    #[cfg(feature = "json")]
+    pub fn to_sessions_json(&self) -> serde_json::Value {
+        let pinned = self.pinned_tabs();
+        let windows: Vec<_> = self
+            .windows()
+            .into_iter()
+            .map(|window| {
+                let tabs: Vec<_> = window
+                    .tabs
+                    .iter()
+                    .map(|tab| {
+                        serde_json::json!({
+                            "id": tab.id,
+                            "url": tab.url,
+                            "title": tab.title,
+                            "transition": tab.transition.to_string(),
+                            "pinned": pinned.contains(&tab.id),
+                        })
+                    })
+                    .collect();
+                serde_json::json!({
+                    "id": window.id,
+                    "selected_index": window.selected_index,
+                    "tabs": tabs,
+                })
+            })
+            .collect();
+        serde_json::json!({ "windows": windows })
+    }
+
+    /// Write every [Content::Tab] as a CSV row, after a header row
+    ///
+    /// The columns are `id`, `index`, `url`, `title`, the `transition` kind (see
+    /// [PageTransitionType::as_str]) and whether the tab is `pinned`.
+    #[cfg(feature = "csv")]
+    pub fn write_tabs_csv<W: std::io::Write>(&self, w: W) -> std::io::Result<()> {
+        let pinned = self.pinned_tabs();
+        let mut writer = csv::Writer::from_writer(w);
+        writer.write_record(["id", "index", "url", "title", "transition", "pinned"])?;
+        for tab in self.tabs() {
+            let transition = match tab.transition.kind() {
+                Ok(kind) => kind.as_str().to_string(),
+                Err(value) => format!("Unknown(0x{value:x})"),
+            };
+            writer.write_record([
+                &tab.id.to_string(),
+                &tab.index.to_string(),
+                &*tab.url,
+                &tab.title,
+                &transition,
+                &pinned.contains(&tab.id).to_string(),
+            ])?;
+        }
+        writer.flush()
+    }
+
+    /// Detach from the parsed data by copying every borrowed string
+    pub fn into_owned(self) -> SNSS<'static> {
+        SNSS {
+            version: self.version,
+            commands: self.commands.into_iter().map(Command::into_owned).collect(),
+        }
+    }
 }
 
+/// Window of a session, see [SNSS::windows]
 #[derive(Debug)]
-pub struct Command {
+pub struct Window<'s, 'a> {
+    pub id: i32,
+    /// Tabs of the window, in their on-screen order
+    pub tabs: Vec<&'s Tab<'a>>,
+    /// Position of the active tab in [Window::tabs]
+    pub selected_index: Option<i32>,
+}
+
+/// Kind of SNSS file, see [SNSS::kind] and [parse_with]
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileKind {
+    /// Current or last session, written by the session service (`Session_*`, `Current Session`)
+    Session,
+    /// Recently closed tabs and windows, written by the tab restore service (`Tabs_*`, `Current Tabs`)
+    Tabs,
+    /// Empty or mixing commands of both services
+    Unknown,
+}
+
+impl FileKind {
+    /// Kind of file from its name in Chrome's `Sessions` directory (`Session_<timestamp>`,
+    /// `Tabs_<timestamp>`), or the `Current`/`Last Session` and `Tabs` of older versions
+    #[cfg(feature = "std")]
+    pub fn from_file_name(name: impl AsRef<OsStr>) -> FileKind {
+        let name = name.as_ref().to_string_lossy();
+        if name.starts_with("Session_") || name.ends_with(" Session") {
+            FileKind::Session
+        } else if name.starts_with("Tabs_") || name.ends_with(" Tabs") {
+            FileKind::Tabs
+        } else {
+            FileKind::Unknown
+        }
+    }
+
+    fn from_ids(ids: impl IntoIterator<Item = u8>) -> FileKind {
+        let (mut session, mut tabs, mut navigation) = (false, false, false);
+        for id in ids {
+            match id {
+                1 | 3 | 4 => tabs = true,
+                0 | 14..=35 => session = true,
+                6 => navigation = true,
+                _ => {}
+            }
+        }
+        match (session, tabs) {
+            (true, false) => FileKind::Session,
+            (false, true) => FileKind::Tabs,
+            (false, false) if navigation => FileKind::Session,
+            _ => FileKind::Unknown,
+        }
+    }
+
+    /// Guess the kind of `data` from the ids of its commands, without decoding them
+    fn detect(data: &[u8]) -> FileKind {
+        // skip the header
+        let mut input = data.get(8..).unwrap_or_default();
+        FileKind::from_ids(core::iter::from_fn(|| {
+            let (len, rest) = input.split_first_chunk::<2>()?;
+            let frame = rest.get(..u16::from_le_bytes(*len) as usize)?;
+            input = &rest[frame.len()..];
+            frame.first().copied()
+        }))
+    }
+}
+
+/// What the version of a SNSS file tells about it, see [SNSS::version_info]
+///
+/// Session and Tabs files share their versions, see [SNSS::kind] to tell them apart.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VersionInfo {
+    /// `kFileVersion1`, supported
+    Version1,
+    /// `kFileVersionWithMarker`, supported: a marker command follows the initial state
+    WithMarker,
+    /// `kEncryptedFileVersion` or `kEncryptedFileVersionWithMarker`, not supported
+    Encrypted { with_marker: bool },
+    /// Not written by any known Chrome release
+    Unknown(i32),
+}
+
+impl VersionInfo {
+    pub fn from_version(version: i32) -> VersionInfo {
+        match version {
+            1 => VersionInfo::Version1,
+            2 => VersionInfo::Encrypted { with_marker: false },
+            3 => VersionInfo::WithMarker,
+            4 => VersionInfo::Encrypted { with_marker: true },
+            version => VersionInfo::Unknown(version),
+        }
+    }
+
+    /// Whether the version is one of [SUPPORTED_VERSIONS]
+    pub fn is_supported(self) -> bool {
+        matches!(self, VersionInfo::Version1 | VersionInfo::WithMarker)
+    }
+}
+
+#[derive(Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command<'a> {
     pub id: u8,
-    pub content: Content,
+    pub content: Content<'a>,
+    /// Position of the id byte in the parsed data
+    pub offset: usize,
+    /// Length of the payload following the id, as declared by the length prefix
+    pub payload_len: u16,
 }
 
-#[derive(Debug)]
-pub enum Content {
-    Tab(Tab),
-    Other(Vec<u8>),
+impl<'a> Command<'a> {
+    /// Command not read from a file, with an [offset](Command::offset) and a
+    /// [payload_len](Command::payload_len) of 0, eg. to [encode](SNSS::to_bytes) it
+    pub fn new(id: u8, content: Content<'a>) -> Self {
+        Command {
+            id,
+            content,
+            offset: 0,
+            payload_len: 0,
+        }
+    }
+
+    /// Typed version of [Command::id]
+    pub fn command_id(&self) -> CommandId {
+        CommandId::from(self.id)
+    }
+
+    /// Position of the payload the content was decoded from in the parsed data
+    pub fn payload_range(&self) -> Range<usize> {
+        let start = self.offset + 1;
+        start..start + usize::from(self.payload_len)
+    }
+
+    /// Detach from the parsed data by copying every borrowed string
+    pub fn into_owned(self) -> Command<'static> {
+        Command {
+            id: self.id,
+            content: self.content.into_owned(),
+            offset: self.offset,
+            payload_len: self.payload_len,
+        }
+    }
 }
 
-#[derive(Debug)]
-pub struct Tab {
+/// Command ids of Chromium's session service (`session_service_commands.cc`), named after their
+/// `kCommand*` constants
+///
+/// Tabs files are written by the tab restore service which numbers its commands differently.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommandId {
+    SetTabWindow,
+    /// Obsolete, superseded by [CommandId::SetWindowBounds3]
+    SetWindowBounds,
+    SetTabIndexInWindow,
+    /// Obsolete, superseded by [CommandId::TabNavigationPathPruned]
+    TabNavigationPathPrunedFromBack,
+    UpdateTabNavigation,
+    SetSelectedNavigationIndex,
+    SetSelectedTabInIndex,
+    SetWindowType,
+    /// Obsolete, superseded by [CommandId::SetWindowBounds3]
+    SetWindowBounds2,
+    /// Obsolete, superseded by [CommandId::TabNavigationPathPruned]
+    TabNavigationPathPrunedFromFront,
+    SetPinnedState,
+    SetExtensionAppId,
+    SetWindowBounds3,
+    SetWindowAppName,
+    TabClosed,
+    WindowClosed,
+    /// Obsolete, superseded by [CommandId::SetTabUserAgentOverride2]
+    SetTabUserAgentOverride,
+    SessionStorageAssociated,
+    SetActiveWindow,
+    LastActiveTime,
+    /// Obsolete, superseded by [CommandId::SetWindowWorkspace2]
+    SetWindowWorkspace,
+    SetWindowWorkspace2,
+    TabNavigationPathPruned,
+    SetTabGroup,
+    /// Obsolete, superseded by [CommandId::SetTabGroupMetadata2]
+    SetTabGroupMetadata,
+    SetTabGroupMetadata2,
+    SetTabGuid,
+    SetTabUserAgentOverride2,
+    SetTabData,
+    SetWindowUserTitle,
+    SetWindowVisibleOnAllWorkspaces,
+    AddTabExtraData,
+    AddWindowExtraData,
+    SetPlatformSessionId,
+    /// Id this crate doesn't know about
+    Unknown(u8),
+}
+
+impl CommandId {
+    pub fn raw(self) -> u8 {
+        use CommandId::*;
+        match self {
+            SetTabWindow => commands::SET_TAB_WINDOW,
+            SetWindowBounds => commands::SET_WINDOW_BOUNDS,
+            SetTabIndexInWindow => commands::SET_TAB_INDEX_IN_WINDOW,
+            TabNavigationPathPrunedFromBack => commands::TAB_NAVIGATION_PATH_PRUNED_FROM_BACK,
+            UpdateTabNavigation => commands::UPDATE_TAB_NAVIGATION,
+            SetSelectedNavigationIndex => commands::SET_SELECTED_NAVIGATION_INDEX,
+            SetSelectedTabInIndex => commands::SET_SELECTED_TAB_IN_INDEX,
+            SetWindowType => commands::SET_WINDOW_TYPE,
+            SetWindowBounds2 => commands::SET_WINDOW_BOUNDS2,
+            TabNavigationPathPrunedFromFront => commands::TAB_NAVIGATION_PATH_PRUNED_FROM_FRONT,
+            SetPinnedState => commands::SET_PINNED_STATE,
+            SetExtensionAppId => commands::SET_EXTENSION_APP_ID,
+            SetWindowBounds3 => commands::SET_WINDOW_BOUNDS3,
+            SetWindowAppName => commands::SET_WINDOW_APP_NAME,
+            TabClosed => commands::TAB_CLOSED,
+            WindowClosed => commands::WINDOW_CLOSED,
+            SetTabUserAgentOverride => commands::SET_TAB_USER_AGENT_OVERRIDE,
+            SessionStorageAssociated => commands::SESSION_STORAGE_ASSOCIATED,
+            SetActiveWindow => commands::SET_ACTIVE_WINDOW,
+            LastActiveTime => commands::LAST_ACTIVE_TIME,
+            SetWindowWorkspace => commands::SET_WINDOW_WORKSPACE,
+            SetWindowWorkspace2 => commands::SET_WINDOW_WORKSPACE2,
+            TabNavigationPathPruned => commands::TAB_NAVIGATION_PATH_PRUNED,
+            SetTabGroup => commands::SET_TAB_GROUP,
+            SetTabGroupMetadata => commands::SET_TAB_GROUP_METADATA,
+            SetTabGroupMetadata2 => commands::SET_TAB_GROUP_METADATA2,
+            SetTabGuid => commands::SET_TAB_GUID,
+            SetTabUserAgentOverride2 => commands::SET_TAB_USER_AGENT_OVERRIDE2,
+            SetTabData => commands::SET_TAB_DATA,
+            SetWindowUserTitle => commands::SET_WINDOW_USER_TITLE,
+            SetWindowVisibleOnAllWorkspaces => commands::SET_WINDOW_VISIBLE_ON_ALL_WORKSPACES,
+            AddTabExtraData => commands::ADD_TAB_EXTRA_DATA,
+            AddWindowExtraData => commands::ADD_WINDOW_EXTRA_DATA,
+            SetPlatformSessionId => commands::SET_PLATFORM_SESSION_ID,
+            Unknown(id) => id,
+        }
+    }
+}
+
+impl From<u8> for CommandId {
+    fn from(id: u8) -> Self {
+        use CommandId::*;
+        match id {
+            commands::SET_TAB_WINDOW => SetTabWindow,
+            commands::SET_WINDOW_BOUNDS => SetWindowBounds,
+            commands::SET_TAB_INDEX_IN_WINDOW => SetTabIndexInWindow,
+            commands::TAB_NAVIGATION_PATH_PRUNED_FROM_BACK => TabNavigationPathPrunedFromBack,
+            commands::UPDATE_TAB_NAVIGATION => UpdateTabNavigation,
+            commands::SET_SELECTED_NAVIGATION_INDEX => SetSelectedNavigationIndex,
+            commands::SET_SELECTED_TAB_IN_INDEX => SetSelectedTabInIndex,
+            commands::SET_WINDOW_TYPE => SetWindowType,
+            commands::SET_WINDOW_BOUNDS2 => SetWindowBounds2,
+            commands::TAB_NAVIGATION_PATH_PRUNED_FROM_FRONT => TabNavigationPathPrunedFromFront,
+            commands::SET_PINNED_STATE => SetPinnedState,
+            commands::SET_EXTENSION_APP_ID => SetExtensionAppId,
+            commands::SET_WINDOW_BOUNDS3 => SetWindowBounds3,
+            commands::SET_WINDOW_APP_NAME => SetWindowAppName,
+            commands::TAB_CLOSED => TabClosed,
+            commands::WINDOW_CLOSED => WindowClosed,
+            commands::SET_TAB_USER_AGENT_OVERRIDE => SetTabUserAgentOverride,
+            commands::SESSION_STORAGE_ASSOCIATED => SessionStorageAssociated,
+            commands::SET_ACTIVE_WINDOW => SetActiveWindow,
+            commands::LAST_ACTIVE_TIME => LastActiveTime,
+            commands::SET_WINDOW_WORKSPACE => SetWindowWorkspace,
+            commands::SET_WINDOW_WORKSPACE2 => SetWindowWorkspace2,
+            commands::TAB_NAVIGATION_PATH_PRUNED => TabNavigationPathPruned,
+            commands::SET_TAB_GROUP => SetTabGroup,
+            commands::SET_TAB_GROUP_METADATA => SetTabGroupMetadata,
+            commands::SET_TAB_GROUP_METADATA2 => SetTabGroupMetadata2,
+            commands::SET_TAB_GUID => SetTabGuid,
+            commands::SET_TAB_USER_AGENT_OVERRIDE2 => SetTabUserAgentOverride2,
+            commands::SET_TAB_DATA => SetTabData,
+            commands::SET_WINDOW_USER_TITLE => SetWindowUserTitle,
+            commands::SET_WINDOW_VISIBLE_ON_ALL_WORKSPACES => SetWindowVisibleOnAllWorkspaces,
+            commands::ADD_TAB_EXTRA_DATA => AddTabExtraData,
+            commands::ADD_WINDOW_EXTRA_DATA => AddWindowExtraData,
+            commands::SET_PLATFORM_SESSION_ID => SetPlatformSessionId,
+            id => Unknown(id),
+        }
+    }
+}
+
+/// Microseconds between the Windows epoch (1601-01-01 UTC) and the Unix epoch
+#[cfg(any(feature = "std", feature = "time"))]
+const UNIX_EPOCH_CHROME_TIME: i64 = 11_644_473_600_000_000;
+
+/// Convert a Chrome timestamp (microseconds since the Windows epoch) to a [SystemTime]
+///
+/// Returns [None] if the platform can't represent the time.
+#[cfg(feature = "std")]
+pub fn chrome_time_to_system_time(time: i64) -> Option<SystemTime> {
+    let since_unix_epoch = time.checked_sub(UNIX_EPOCH_CHROME_TIME)?;
+    let duration = Duration::from_micros(since_unix_epoch.unsigned_abs());
+    if since_unix_epoch >= 0 {
+        UNIX_EPOCH.checked_add(duration)
+    } else {
+        UNIX_EPOCH.checked_sub(duration)
+    }
+}
+
+/// Convert a Chrome timestamp (microseconds since the Windows epoch) to a UTC
+/// [OffsetDateTime](time::OffsetDateTime)
+///
+/// Returns [None] if the time is out of the range of [OffsetDateTime](time::OffsetDateTime).
+#[cfg(feature = "time")]
+pub fn chrome_time_to_offset_date_time(time: i64) -> Option<time::OffsetDateTime> {
+    let since_unix_epoch = i128::from(time) - i128::from(UNIX_EPOCH_CHROME_TIME);
+    time::OffsetDateTime::from_unix_timestamp_nanos(since_unix_epoch * 1000).ok()
+}
+
+/// Decoded payload of a [Command].
+///
+/// Command ids follow Chromium's session service (`session_service_commands.cc`) in Session files:
+///
+/// | id     | Chromium command                          | Variant                                  |
+/// |--------|-------------------------------------------|------------------------------------------|
+/// | 0      | `kCommandSetTabWindow`                    | [Content::TabWindow]                     |
+/// | 2      | `kCommandSetTabIndexInWindow`             | [Content::TabIndexInWindow]              |
+/// | 5, 11  | `kCommandTabNavigationPathPrunedFrom*`    | [Content::NavigationPathPruned]          |
+/// | 6      | `kCommandUpdateTabNavigation`             | [Content::Tab]                           |
+/// | 7      | `kCommandSetSelectedNavigationIndex`      | [Content::SelectedNavigationIndex]       |
+/// | 8      | `kCommandSetSelectedTabInIndex`           | [Content::SelectedTabInIndex]            |
+/// | 9      | `kCommandSetWindowType`                   | [Content::WindowType]                    |
+/// | 12     | `kCommandSetPinnedState`                  | [Content::PinnedState]                   |
+/// | 13     | `kCommandSetExtensionAppID`               | [Content::ExtensionAppId]                |
+/// | 14     | `kCommandSetWindowBounds3`                | [Content::WindowBounds]                  |
+/// | 15     | `kCommandSetWindowAppName`                | [Content::WindowAppName]                 |
+/// | 16     | `kCommandTabClosed`                       | [Content::TabClosed]                     |
+/// | 17     | `kCommandWindowClosed`                    | [Content::WindowClosed]                  |
+/// | 18, 29 | `kCommandSetTabUserAgentOverride(2)`      | [Content::TabUserAgentOverride]          |
+/// | 20     | `kCommandSetActiveWindow`                 | [Content::ActiveWindow]                  |
+/// | 21     | `kCommandLastActiveTime`                  | [Content::LastActiveTime]                |
+/// | 23     | `kCommandSetWindowWorkspace2`             | [Content::WindowWorkspace]               |
+/// | 24     | `kCommandTabNavigationPathPruned`         | [Content::TabNavigationPathPruned]       |
+/// | 25     | `kCommandSetTabGroup`                     | [Content::TabGroup]                      |
+/// | 27     | `kCommandSetTabGroupMetadata2`            | [Content::TabGroupMetadata]              |
+/// | 28     | `kCommandSetTabGuid`                      | [Content::TabGuid]                       |
+/// | 31     | `kCommandSetWindowUserTitle`              | [Content::WindowUserTitle]               |
+/// | 32     | `kCommandSetWindowVisibleOnAllWorkspaces` | [Content::WindowVisibleOnAllWorkspaces]  |
+///
+/// Every other id, as well as a malformed `kCommandSetExtensionAppID` or a `kCommandSetTabGuid`
+/// without a well-formed GUID, is kept verbatim as [Content::Other].
+///
+/// Tabs files are written by the tab restore service (`tab_restore_service_impl.cc`), of which only
+/// `kCommandUpdateTabNavigation` (id 1) and `kCommandSetExtensionAppID` (id 6) are decoded, as
+/// [Content::Tab] and [Content::ExtensionAppId].
+#[derive(Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Content<'a> {
+    Tab(Tab<'a>),
+    /// The tab belongs to the window
+    TabWindow {
+        window_id: i32,
+        tab_id: i32,
+    },
+    /// Position of the tab within its window
+    TabIndexInWindow {
+        tab_id: i32,
+        index: i32,
+    },
+    /// The navigation entry at `index` (see [Tab::index]) is the one shown in the tab
+    SelectedNavigationIndex {
+        tab_id: i32,
+        index: i32,
+    },
+    /// The tab at `index` is the active one in the window
+    SelectedTabInIndex {
+        window_id: i32,
+        index: i32,
+    },
+    /// Kind of the window, see [WindowType::as_known]
+    WindowType {
+        window_id: i32,
+        window_type: i32,
+    },
+    /// The tab was pinned or unpinned
+    PinnedState {
+        tab_id: i32,
+        pinned: bool,
+    },
+    /// The tab belongs to a Chrome app or extension (eg. a PWA)
+    ExtensionAppId {
+        tab_id: i32,
+        extension_app_id: Cow<'a, str>,
+    },
+    /// On-screen geometry of the window
+    WindowBounds {
+        window_id: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        /// See [WindowShowState::as_known]
+        show_state: i32,
+    },
+    /// Name of the app shown in an app window
+    WindowAppName {
+        window_id: i32,
+        app_name: Cow<'a, str>,
+    },
+    /// The tab was closed by the user
+    TabClosed {
+        tab_id: i32,
+        /// Microseconds since the Windows epoch (1601-01-01 UTC), as in Chrome's `base::Time`
+        close_time: i64,
+    },
+    /// The window was closed by the user
+    WindowClosed {
+        window_id: i32,
+        /// Microseconds since the Windows epoch (1601-01-01 UTC), as in Chrome's `base::Time`
+        close_time: i64,
+    },
+    /// User-agent string the tab overrides the default with (see [Tab::user_agent])
+    TabUserAgentOverride {
+        tab_id: i32,
+        user_agent: Cow<'a, str>,
+        /// Raw trailing bytes of `kCommandSetTabUserAgentOverride2`: whether the client hints are
+        /// overridden too, then the serialized `blink::UserAgentMetadata` if so
+        #[cfg_attr(feature = "serde", serde(with = "base64_bytes::option"))]
+        client_hints: Option<Vec<u8>>,
+    },
+    /// The window had the focus
+    ActiveWindow {
+        window_id: i32,
+    },
+    /// The tab was last activated at `last_active_time`
+    LastActiveTime {
+        tab_id: i32,
+        /// Microseconds since the Windows epoch (1601-01-01 UTC), see [chrome_time_to_system_time]
+        last_active_time: i64,
+    },
+    /// Navigation entries were removed from the front or the back of the tab's history, as written
+    /// before [Content::TabNavigationPathPruned]
+    NavigationPathPruned {
+        tab_id: i32,
+        /// Number of entries removed from the front, or kept at the front if removing from the back
+        count: i32,
+        /// Whether the command is `kCommandTabNavigationPathPrunedFromFront` (id 11)
+        from_front: bool,
+    },
+    /// `count` navigation entries starting at `index` were removed from the tab's history
+    TabNavigationPathPruned {
+        tab_id: i32,
+        index: i32,
+        count: i32,
+    },
+    /// The tab was added to or removed from a group
+    TabGroup {
+        tab_id: i32,
+        /// Token identifying the group (see [Content::TabGroupMetadata]), [None] if the tab isn't
+        /// grouped anymore
+        group: Option<[u8; 16]>,
+    },
+    /// Appearance of a tab group
+    TabGroupMetadata {
+        /// `base::Token` of the group, its high then low 64 bits halves in little-endian
+        group: [u8; 16],
+        title: String,
+        /// Chromium's `tab_groups::TabGroupColorId` (grey, blue, red, ...)
+        color: i32,
+        collapsed: bool,
+    },
+    /// Stable identifier of the tab, kept across restores
+    TabGuid {
+        tab_id: i32,
+        /// Hyphenated GUID, eg. `123e4567-e89b-12d3-a456-426614174000`
+        guid: Cow<'a, str>,
+    },
+    /// Virtual desktop the window is on
+    WindowWorkspace {
+        window_id: i32,
+        workspace: Cow<'a, str>,
+    },
+    /// Name given to the window by the user
+    WindowUserTitle {
+        window_id: i32,
+        title: Cow<'a, str>,
+    },
+    /// The window is shown on every virtual desktop
+    WindowVisibleOnAllWorkspaces {
+        window_id: i32,
+        visible_on_all_workspaces: bool,
+    },
+    Other(#[cfg_attr(feature = "serde", serde(with = "base64_bytes"))] Vec<u8>),
+}
+
+impl Content<'_> {
+    /// Detach from the parsed data by copying every borrowed string
+    pub fn into_owned(self) -> Content<'static> {
+        match self {
+            Content::Tab(tab) => Content::Tab(tab.into_owned()),
+            Content::TabWindow { window_id, tab_id } => Content::TabWindow { window_id, tab_id },
+            Content::TabIndexInWindow { tab_id, index } => {
+                Content::TabIndexInWindow { tab_id, index }
+            }
+            Content::SelectedNavigationIndex { tab_id, index } => {
+                Content::SelectedNavigationIndex { tab_id, index }
+            }
+            Content::SelectedTabInIndex { window_id, index } => {
+                Content::SelectedTabInIndex { window_id, index }
+            }
+            Content::WindowType {
+                window_id,
+                window_type,
+            } => Content::WindowType {
+                window_id,
+                window_type,
+            },
+            Content::PinnedState { tab_id, pinned } => Content::PinnedState { tab_id, pinned },
+            Content::ExtensionAppId {
+                tab_id,
+                extension_app_id,
+            } => Content::ExtensionAppId {
+                tab_id,
+                extension_app_id: Cow::Owned(extension_app_id.into_owned()),
+            },
+            Content::WindowBounds {
+                window_id,
+                x,
+                y,
+                width,
+                height,
+                show_state,
+            } => Content::WindowBounds {
+                window_id,
+                x,
+                y,
+                width,
+                height,
+                show_state,
+            },
+            Content::WindowAppName {
+                window_id,
+                app_name,
+            } => Content::WindowAppName {
+                window_id,
+                app_name: Cow::Owned(app_name.into_owned()),
+            },
+            Content::TabClosed { tab_id, close_time } => Content::TabClosed { tab_id, close_time },
+            Content::WindowClosed {
+                window_id,
+                close_time,
+            } => Content::WindowClosed {
+                window_id,
+                close_time,
+            },
+            Content::TabUserAgentOverride {
+                tab_id,
+                user_agent,
+                client_hints,
+            } => Content::TabUserAgentOverride {
+                tab_id,
+                user_agent: Cow::Owned(user_agent.into_owned()),
+                client_hints,
+            },
+            Content::ActiveWindow { window_id } => Content::ActiveWindow { window_id },
+            Content::LastActiveTime {
+                tab_id,
+                last_active_time,
+            } => Content::LastActiveTime {
+                tab_id,
+                last_active_time,
+            },
+            Content::NavigationPathPruned {
+                tab_id,
+                count,
+                from_front,
+            } => Content::NavigationPathPruned {
+                tab_id,
+                count,
+                from_front,
+            },
+            Content::TabNavigationPathPruned {
+                tab_id,
+                index,
+                count,
+            } => Content::TabNavigationPathPruned {
+                tab_id,
+                index,
+                count,
+            },
+            Content::TabGroup { tab_id, group } => Content::TabGroup { tab_id, group },
+            Content::TabGroupMetadata {
+                group,
+                title,
+                color,
+                collapsed,
+            } => Content::TabGroupMetadata {
+                group,
+                title,
+                color,
+                collapsed,
+            },
+            Content::TabGuid { tab_id, guid } => Content::TabGuid {
+                tab_id,
+                guid: Cow::Owned(guid.into_owned()),
+            },
+            Content::WindowWorkspace {
+                window_id,
+                workspace,
+            } => Content::WindowWorkspace {
+                window_id,
+                workspace: Cow::Owned(workspace.into_owned()),
+            },
+            Content::WindowUserTitle { window_id, title } => Content::WindowUserTitle {
+                window_id,
+                title: Cow::Owned(title.into_owned()),
+            },
+            Content::WindowVisibleOnAllWorkspaces {
+                window_id,
+                visible_on_all_workspaces,
+            } => Content::WindowVisibleOnAllWorkspaces {
+                window_id,
+                visible_on_all_workspaces,
+            },
+            Content::Other(payload) => Content::Other(payload),
+        }
+    }
+}
+
+/// A tab can be built with [TabBuilder] or from [Tab::default], which has empty strings and a
+/// [PageTransition] of 0
+///
+/// The fields after [transition](Tab::transition) were appended over Chrome releases, a tab
+/// written by an older release ends before them and they keep their default value, like Chrome
+/// does. The file [version](SNSS::version) doesn't tell the layout of a tab apart.
+#[derive(Default, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tab<'a> {
     pub id: i32,
     /// Index in this tab’s back-forward list
     pub index: i32,
-    pub url: String,
+    pub url: Cow<'a, str>,
     pub title: String,
+    #[cfg_attr(feature = "serde", serde(with = "base64_bytes"))]
     pub state: Vec<u8>,
     pub transition: PageTransition,
     /// The page has POST data
     pub post: bool,
-    pub referrer_url: String,
+    pub referrer_url: Cow<'a, str>,
     pub reference_policy: i32,
-    pub original_request_url: String,
+    pub original_request_url: Cow<'a, str>,
     /// The user-agent was overridden
     pub user_agent: bool,
+    /// Fields following [Tab::user_agent] that aren't decoded (timestamp, favicon, http status,
+    /// ...), kept for [SNSS::to_bytes]
+    #[cfg_attr(feature = "serde", serde(with = "base64_bytes"))]
+    pub extra: Vec<u8>,
+}
+
+impl Tab<'_> {
+    /// The page was reloaded, which includes restoring it from a previous session
+    ///
+    /// ```no_run
+    /// # let data = std::fs::read("Session")?;
+    /// # let snss = snss::parse(&data)?;
+    /// let restored = snss.tabs().filter(|tab| tab.is_reload()).count();
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn is_reload(&self) -> bool {
+        self.transition.kind() == Ok(PageTransitionType::Reload)
+    }
+
+    /// The page is the result of submitting a form
+    ///
+    /// ```no_run
+    /// # let data = std::fs::read("Session")?;
+    /// # let snss = snss::parse(&data)?;
+    /// for tab in snss.tabs().filter(|tab| tab.is_form_submit()) {
+    ///     println!("{} (post: {})", tab.url, tab.post);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn is_form_submit(&self) -> bool {
+        self.transition.kind() == Ok(PageTransitionType::FormSubmit)
+    }
+
+    /// The page was reached through a client or server redirect
+    ///
+    /// ```no_run
+    /// # let data = std::fs::read("Session")?;
+    /// # let snss = snss::parse(&data)?;
+    /// for tab in snss.tabs().filter(|tab| tab.came_from_redirect()) {
+    ///     println!("{} redirected to {}", tab.original_request_url, tab.url);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn came_from_redirect(&self) -> bool {
+        self.transition.is_redirect()
+    }
+
+    /// Decode [Tab::state]
+    pub fn page_state(&self) -> Result<PageState, Error> {
+        PageState::parse(&self.state)
+    }
+
+    /// [Tab::url] without its tracking query parameters (`utm_*`, `gclid`, `fbclid`, ...), eg. to
+    /// deduplicate tabs
+    ///
+    /// The other parameters are kept byte for byte, and an url without tracking parameters or that
+    /// can't be parsed is returned as is.
+    #[cfg(feature = "url")]
+    pub fn clean_url(&self) -> String {
+        let Ok(mut url) = url::Url::parse(&self.url) else {
+            return self.url.to_string();
+        };
+        let Some(query) = url.query() else {
+            return self.url.to_string();
+        };
+        let is_tracking = |segment: &&str| {
+            url::form_urlencoded::parse(segment.as_bytes())
+                .next()
+                .is_some_and(|(name, _)| is_tracking_param(&name))
+        };
+        if !query.split('&').any(|segment| is_tracking(&segment)) {
+            return self.url.to_string();
+        }
+        let kept = query
+            .split('&')
+            .filter(|segment| !is_tracking(segment))
+            .collect::<Vec<_>>()
+            .join("&");
+        url.set_query((!kept.is_empty()).then_some(&kept));
+        url.into()
+    }
+
+    /// Detach from the parsed data by copying every borrowed string
+    pub fn into_owned(self) -> Tab<'static> {
+        Tab {
+            url: Cow::Owned(self.url.into_owned()),
+            referrer_url: Cow::Owned(self.referrer_url.into_owned()),
+            original_request_url: Cow::Owned(self.original_request_url.into_owned()),
+            ..self
+        }
+    }
+}
+
+/// Builder of a [Tab], starting from [Tab::default]
+///
+/// ```
+/// # use snss::{Command, CommandId, Content, PageTransition, SNSS, TabBuilder};
+/// let tab = TabBuilder::new()
+///     .id(1)
+///     .url("https://example.com/")
+///     .title("Example")
+///     .transition(PageTransition(0x01000000))
+///     .build();
+/// let mut snss = SNSS::new(3);
+/// snss.push(Command::new(CommandId::UpdateTabNavigation.raw(), Content::Tab(tab)));
+/// ```
+#[derive(Default, Debug)]
+pub struct TabBuilder<'a> {
+    tab: Tab<'a>,
+}
+
+impl<'a> TabBuilder<'a> {
+    pub fn new() -> Self {
+        TabBuilder::default()
+    }
+
+    pub fn id(mut self, id: i32) -> Self {
+        self.tab.id = id;
+        self
+    }
+
+    pub fn index(mut self, index: i32) -> Self {
+        self.tab.index = index;
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<Cow<'a, str>>) -> Self {
+        self.tab.url = url.into();
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.tab.title = title.into();
+        self
+    }
+
+    pub fn state(mut self, state: Vec<u8>) -> Self {
+        self.tab.state = state;
+        self
+    }
+
+    pub fn transition(mut self, transition: PageTransition) -> Self {
+        self.tab.transition = transition;
+        self
+    }
+
+    pub fn post(mut self, post: bool) -> Self {
+        self.tab.post = post;
+        self
+    }
+
+    pub fn referrer_url(mut self, referrer_url: impl Into<Cow<'a, str>>) -> Self {
+        self.tab.referrer_url = referrer_url.into();
+        self
+    }
+
+    pub fn reference_policy(mut self, reference_policy: i32) -> Self {
+        self.tab.reference_policy = reference_policy;
+        self
+    }
+
+    pub fn original_request_url(mut self, original_request_url: impl Into<Cow<'a, str>>) -> Self {
+        self.tab.original_request_url = original_request_url.into();
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: bool) -> Self {
+        self.tab.user_agent = user_agent;
+        self
+    }
+
+    pub fn build(self) -> Tab<'a> {
+        self.tab
+    }
+}
+
+/// Query parameters removed by [Tab::clean_url]
+#[cfg(feature = "url")]
+fn is_tracking_param(name: &str) -> bool {
+    const PARAMS: [&str; 9] = [
+        "gclid", "dclid", "fbclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_ga",
+    ];
+    name.starts_with("utm_") || PARAMS.contains(&name)
+}
+
+/// Markdown link `[title](url)`, or only the url for an untitled tab
+impl Display for Tab<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.title.is_empty() {
+            return write!(f, "{}", self.url);
+        }
+        write!(f, "[")?;
+        for c in self.title.chars() {
+            if matches!(c, ']' | ')') {
+                write!(f, "\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        write!(f, "]({})", self.url)
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Serialized with its [kind](PageTransition::kind) and [qualifiers](PageTransition::qualifiers)
+/// next to the raw `value`, only the latter is read back
+#[derive(Clone, Copy, Default, Eq, PartialEq, Hash, Debug)]
 pub struct PageTransition(pub u32);
 
-impl PageTransition {
-    pub fn kind(self) -> std::result::Result<PageTransitionType, u8> {
-        use PageTransitionType::*;
-        match (self.0 & 0xFF) as u8 {
-            0 => Ok(Link),
-            1 => Ok(Typed),
-            2 => Ok(AutoBookmark),
-            3 => Ok(AutoSubframe),
-            4 => Ok(ManualSubframe),
-            5 => Ok(Generated),
-            6 => Ok(StartPage),
-            7 => Ok(FormSubmit),
-            8 => Ok(Reload),
-            9 => Ok(Keyword),
-            10 => Ok(KeywordGenerated),
-            id => Err(id),
+#[cfg(feature = "serde")]
+impl serde::Serialize for PageTransition {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("PageTransition", 3)?;
+        state.serialize_field("value", &self.0)?;
+        state.serialize_field("kind", &self.kind().ok())?;
+        state.serialize_field("qualifiers", &self.qualifiers())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PageTransition {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "PageTransition")]
+        struct Value {
+            value: u32,
         }
+
+        Value::deserialize(deserializer).map(|transition| PageTransition(transition.value))
+    }
+}
+
+impl PageTransition {
+    pub fn kind(self) -> core::result::Result<PageTransitionType, u8> {
+        PageTransitionType::try_from(self.core_type())
+    }
+
+    /// Core value, as decoded by [PageTransition::kind]
+    pub fn core_type(self) -> u8 {
+        (self.0 & 0xFF) as u8
+    }
+
+    /// Qualifier bits, as decoded by [PageTransition::qualifiers]
+    pub fn qualifier_bits(self) -> u32 {
+        self.0 & 0xFFFFFF00
+    }
+
+    /// Qualifiers as a set of flags
+    #[cfg(feature = "bitflags")]
+    pub fn qualifier_flags(self) -> PageTransitionQualifierFlags {
+        PageTransitionQualifierFlags::from_bits_truncate(self.0)
+    }
+
+    /// Either a client or a server redirect (Chromium's `PAGE_TRANSITION_IS_REDIRECT_MASK`)
+    pub fn is_redirect(self) -> bool {
+        self.0 & 0xC0000000 != 0
+    }
+
+    /// Not a navigation of a sub frame
+    pub fn is_main_frame(self) -> bool {
+        !matches!(
+            self.kind(),
+            Ok(PageTransitionType::AutoSubframe | PageTransitionType::ManualSubframe)
+        )
     }
 
     pub fn qualifiers(self) -> PageTransitionQualifiers {
         PageTransitionQualifiers {
             back_forward: (self.0 & 0x01000000) == 0x01000000,
             address_bar: (self.0 & 0x02000000) == 0x02000000,
-            homepage: (self.0 & 0x04000000) != 0x04000000,
-            chain_start: (self.0 & 0x10000000) != 0x10000000,
-            redirect_chain_end: (self.0 & 0x20000000) != 0x20000000,
-            client_redirect: (self.0 & 0x40000000) != 0x40000000,
-            server_redirect: (self.0 & 0x80000000) != 0x80000000,
+            homepage: (self.0 & 0x04000000) == 0x04000000,
+            chain_start: (self.0 & 0x10000000) == 0x10000000,
+            redirect_chain_end: (self.0 & 0x20000000) == 0x20000000,
+            client_redirect: (self.0 & 0x40000000) == 0x40000000,
+            server_redirect: (self.0 & 0x80000000) == 0x80000000,
+        }
+    }
+}
+
+/// Kind followed by the qualifiers set, eg. `reload [client_redirect, server_redirect]`
+impl Display for PageTransition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind() {
+            Ok(kind) => write!(f, "{kind}")?,
+            Err(value) => write!(f, "Unknown(0x{value:x})")?,
         }
+        let q = self.qualifiers();
+        let qualifiers = [
+            (q.back_forward, "back_forward"),
+            (q.address_bar, "address_bar"),
+            (q.homepage, "homepage"),
+            (q.chain_start, "chain_start"),
+            (q.redirect_chain_end, "redirect_chain_end"),
+            (q.client_redirect, "client_redirect"),
+            (q.server_redirect, "server_redirect"),
+        ];
+        let mut separator = " [";
+        for (_, name) in qualifiers.iter().filter(|(set, _)| *set) {
+            write!(f, "{separator}{name}")?;
+            separator = ", ";
+        }
+        if separator == ", " {
+            write!(f, "]")?;
+        }
+        Ok(())
     }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PageTransitionType {
     /// User arrived at this page by clicking a link on another page
     Link = 0,
@@ -145,7 +1942,86 @@ pub enum PageTransitionType {
     KeywordGenerated = 10,
 }
 
+impl PageTransitionType {
+    /// Name of the core transition in Chromium (`PageTransitionGetCoreTransitionString`), eg.
+    /// `auto_bookmark`, parsed back by [FromStr](core::str::FromStr)
+    pub fn as_str(self) -> &'static str {
+        use PageTransitionType::*;
+        match self {
+            Link => "link",
+            Typed => "typed",
+            AutoBookmark => "auto_bookmark",
+            AutoSubframe => "auto_subframe",
+            ManualSubframe => "manual_subframe",
+            Generated => "generated",
+            StartPage => "auto_toplevel",
+            FormSubmit => "form_submit",
+            Reload => "reload",
+            Keyword => "keyword",
+            KeywordGenerated => "keyword_generated",
+        }
+    }
+}
+
+/// See [PageTransitionType::as_str]
+impl Display for PageTransitionType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Either the [Display] name or the variant name, ignoring case
+impl core::str::FromStr for PageTransitionType {
+    type Err = UnknownPageTransitionType;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        (0..=u8::MAX)
+            .map_while(|value| PageTransitionType::try_from(value).ok())
+            .find(|kind| {
+                s.eq_ignore_ascii_case(kind.as_str())
+                    || s.eq_ignore_ascii_case(&format!("{kind:?}"))
+            })
+            .ok_or_else(|| UnknownPageTransitionType(s.to_string()))
+    }
+}
+
+/// Error of parsing a [PageTransitionType] from a string, holding that string
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct UnknownPageTransitionType(pub String);
+
+impl Display for UnknownPageTransitionType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unknown page transition type '{}'", self.0)
+    }
+}
+
+impl core::error::Error for UnknownPageTransitionType {}
+
+/// Core value of a [PageTransition], failing with the value if it is unknown
+impl TryFrom<u8> for PageTransitionType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> core::result::Result<Self, Self::Error> {
+        use PageTransitionType::*;
+        match value {
+            0 => Ok(Link),
+            1 => Ok(Typed),
+            2 => Ok(AutoBookmark),
+            3 => Ok(AutoSubframe),
+            4 => Ok(ManualSubframe),
+            5 => Ok(Generated),
+            6 => Ok(StartPage),
+            7 => Ok(FormSubmit),
+            8 => Ok(Reload),
+            9 => Ok(Keyword),
+            10 => Ok(KeywordGenerated),
+            value => Err(value),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PageTransitionQualifiers {
     /// User used the back or forward buttons to arrive at this page
     pub back_forward: bool,
@@ -163,76 +2039,635 @@ pub struct PageTransitionQualifiers {
     pub server_redirect: bool,
 }
 
-fn parse_snss(s: &mut &Bytes) -> winnow::Result<SNSS> {
-    seq! { SNSS {
-        _: b"SNSS",
-        version: le_i32,
-        commands: winnow::combinator::repeat(0.., length_and_then(le_u16, parse_command)),
+#[cfg(feature = "bitflags")]
+bitflags::bitflags! {
+    /// [PageTransitionQualifiers] as a set of flags, see [PageTransition::qualifier_flags]
+    #[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PageTransitionQualifierFlags: u32 {
+        /// User used the back or forward buttons to arrive at this page
+        const BACK_FORWARD = 0x01000000;
+        /// User used the address bar to trigger this navigation
+        const ADDRESS_BAR = 0x02000000;
+        /// User is navigating to the homepage
+        const HOMEPAGE = 0x04000000;
+        /// The beginning of a navigation chain
+        const CHAIN_START = 0x10000000;
+        /// Last transition in a redirect chain
+        const REDIRECT_CHAIN_END = 0x20000000;
+        /// Transition was a client-side redirect (eg. caused by JavaScript or a meta-tag redirect)
+        const CLIENT_REDIRECT = 0x40000000;
+        /// Transition was a server-side redirect (ie a redirect specified in the HTTP response header)
+        const SERVER_REDIRECT = 0x80000000;
+    }
+}
 
-    }}
+/// Chromium's `SessionWindow::WindowType`
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[repr(i32)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowType {
+    /// Regular browser window with a tab strip
+    Normal = 0,
+    /// Popup window (eg. opened with `window.open` and window features)
+    Popup = 1,
+    /// Window of an installed web app
+    App = 2,
+    /// Developer tools window
+    Devtools = 3,
+    /// Popup opened by an installed web app
+    AppPopup = 4,
+    /// Custom tab (ChromeOS ARC++ apps)
+    CustomTab = 5,
+}
+
+impl WindowType {
+    pub fn as_known(window_type: i32) -> core::result::Result<WindowType, i32> {
+        use WindowType::*;
+        match window_type {
+            0 => Ok(Normal),
+            1 => Ok(Popup),
+            2 => Ok(App),
+            3 => Ok(Devtools),
+            4 => Ok(AppPopup),
+            5 => Ok(CustomTab),
+            id => Err(id),
+        }
+    }
+}
+
+/// Chromium's `ui::mojom::WindowShowState`
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[repr(i32)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowShowState {
+    /// Left to the platform
+    Default = 0,
+    Normal = 1,
+    Minimized = 2,
+    Maximized = 3,
+    /// Shown without being activated
+    Inactive = 4,
+    Fullscreen = 5,
+}
+
+impl WindowShowState {
+    pub fn as_known(show_state: i32) -> core::result::Result<WindowShowState, i32> {
+        use WindowShowState::*;
+        match show_state {
+            0 => Ok(Default),
+            1 => Ok(Normal),
+            2 => Ok(Minimized),
+            3 => Ok(Maximized),
+            4 => Ok(Inactive),
+            5 => Ok(Fullscreen),
+            id => Err(id),
+        }
+    }
+}
+
+/// Serialize opaque byte blobs as base64 strings, which is much more compact than a JSON array
+#[cfg(feature = "serde")]
+mod base64_bytes {
+    use alloc::{string::String, vec::Vec};
+
+    use base64::{Engine, prelude::BASE64_STANDARD};
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        BASE64_STANDARD.decode(encoded).map_err(D::Error::custom)
+    }
+
+    /// Same for an optional blob
+    pub mod option {
+        use alloc::{string::String, vec::Vec};
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            bytes: &Option<Vec<u8>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match bytes {
+                Some(bytes) => super::serialize(bytes, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Vec<u8>>, D::Error> {
+            use base64::{Engine, prelude::BASE64_STANDARD};
+            use serde::de::Error;
+
+            Option::<String>::deserialize(deserializer)?
+                .map(|encoded| BASE64_STANDARD.decode(encoded).map_err(D::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+fn check_version(version: i32) -> Result<(), Error> {
+    if SUPPORTED_VERSIONS.contains(&version) {
+        return Ok(());
+    }
+    Err(Error {
+        kind: ErrorKind::UnsupportedVersion(version),
+        message: format!("unsupported SNSS version {version}"),
+        offset: 4,
+        context: vec!["version"],
+        command_index: None,
+    })
+}
+
+/// Magic bytes followed by the version
+fn parse_header(s: &mut &Bytes) -> winnow::Result<i32> {
+    preceded(
+        b"SNSS".context(StrContext::Label("magic")),
+        le_i32.context(StrContext::Label("version")),
+    )
     .parse_next(s)
 }
 
-fn parse_command<'s>(s: &mut &'s Bytes) -> winnow::Result<Command> {
-    trace("Command", |s: &mut &'s Bytes| {
+/// Decode the command framed by `frame`, whose id is at `start` in the parsed data
+///
+/// An error is reported at the offset of the field that failed.
+fn parse_frame(options: ParseOptions, start: usize, frame: &[u8]) -> Result<Command<'_>, Error> {
+    let mut input = Bytes::new(frame);
+    parse_command(options, start)
+        .parse_next(&mut input)
+        .map_err(|err| Error::from_context(start + frame.len() - input.len(), err))
+}
+
+/// Command whose id is at `offset` in the parsed data, for the kind of file set in `options`
+fn parse_command<'s>(
+    options: ParseOptions,
+    offset: usize,
+) -> impl Parser<&'s Bytes, Command<'s>, ContextError> {
+    let kind = options.kind.unwrap_or(FileKind::Unknown);
+    let parse_tab = parse_tab(options.lossy);
+    use commands::*;
+
+    trace("Command", move |s: &mut &'s Bytes| {
         let id = le_u8.parse_next(s)?;
+        // the frame is at most u16::MAX bytes long, id included
+        let payload_len = s.len() as u16;
 
-        let content = if id == 1 || id == 6 {
-            parse_tab.map(Content::Tab).parse_next(s)?
-        } else {
-            Content::Other(s.to_vec())
+        let content = match id {
+            tabs::UPDATE_TAB_NAVIGATION if kind != FileKind::Session => {
+                parse_tab.map(Content::Tab).parse_next(s)?
+            }
+            tabs::SET_EXTENSION_APP_ID if kind == FileKind::Tabs => {
+                alt((parse_extension_app_id, parse_other)).parse_next(s)?
+            }
+            _ if kind == FileKind::Tabs => parse_other.parse_next(s)?,
+            SET_TAB_WINDOW => parse_tab_window.parse_next(s)?,
+            UPDATE_TAB_NAVIGATION => parse_tab.map(Content::Tab).parse_next(s)?,
+            SET_TAB_INDEX_IN_WINDOW => parse_tab_index_in_window.parse_next(s)?,
+            TAB_NAVIGATION_PATH_PRUNED_FROM_BACK => {
+                parse_navigation_path_pruned(false).parse_next(s)?
+            }
+            TAB_NAVIGATION_PATH_PRUNED_FROM_FRONT => {
+                parse_navigation_path_pruned(true).parse_next(s)?
+            }
+            SET_SELECTED_NAVIGATION_INDEX => parse_selected_navigation_index.parse_next(s)?,
+            SET_SELECTED_TAB_IN_INDEX => parse_selected_tab_in_index.parse_next(s)?,
+            SET_WINDOW_TYPE => parse_window_type.parse_next(s)?,
+            SET_PINNED_STATE => parse_pinned_state.parse_next(s)?,
+            SET_EXTENSION_APP_ID => alt((parse_extension_app_id, parse_other)).parse_next(s)?,
+            SET_WINDOW_BOUNDS3 if s.len() == 24 => parse_window_bounds.parse_next(s)?,
+            SET_WINDOW_APP_NAME => parse_window_app_name.parse_next(s)?,
+            TAB_CLOSED => parse_tab_closed.parse_next(s)?,
+            WINDOW_CLOSED => parse_window_closed.parse_next(s)?,
+            SET_TAB_USER_AGENT_OVERRIDE | SET_TAB_USER_AGENT_OVERRIDE2 => {
+                parse_tab_user_agent_override.parse_next(s)?
+            }
+            SET_ACTIVE_WINDOW => parse_active_window.parse_next(s)?,
+            LAST_ACTIVE_TIME => parse_last_active_time.parse_next(s)?,
+            SET_WINDOW_WORKSPACE2 => parse_window_workspace.parse_next(s)?,
+            TAB_NAVIGATION_PATH_PRUNED => parse_tab_navigation_path_pruned.parse_next(s)?,
+            SET_TAB_GROUP => parse_tab_group.parse_next(s)?,
+            SET_TAB_GROUP_METADATA2 => parse_tab_group_metadata(options.lossy).parse_next(s)?,
+            SET_TAB_GUID => alt((parse_tab_guid, parse_other)).parse_next(s)?,
+            SET_WINDOW_USER_TITLE => parse_window_user_title.parse_next(s)?,
+            SET_WINDOW_VISIBLE_ON_ALL_WORKSPACES => {
+                parse_window_visible_on_all_workspaces.parse_next(s)?
+            }
+            _ => parse_other.parse_next(s)?,
         };
 
-        Ok(Command { id, content })
+        Ok(Command {
+            id,
+            content,
+            offset,
+            payload_len,
+        })
     })
-    .parse_next(s)
 }
 
-fn parse_tab(s: &mut &Bytes) -> winnow::Result<Tab> {
+fn parse_other<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    rest.map(|s: &[u8]| Content::Other(s.to_vec()))
+        .parse_next(s)
+}
+
+/// Tab whose strings are decoded with [parse_padded_utf8_lossy] and [parse_padded_utf16_lossy]
+/// if `lossy`
+fn parse_tab<'s>(lossy: bool) -> impl Parser<&'s Bytes, Tab<'s>, ContextError> + Copy {
+    let utf8 = move |s: &mut &'s Bytes| match lossy {
+        true => parse_padded_utf8_lossy(s),
+        false => parse_padded_utf8(s),
+    };
+    let utf16 = move |s: &mut &'s Bytes| match lossy {
+        true => parse_padded_utf16_lossy(s),
+        false => parse_padded_utf16(s),
+    };
     // next_multiple_of(4) for ensuring 4-bytes alignment
-    seq! { Tab {
-        _ : take(4usize),
-        id: le_i32.context(StrContext::Label("id")),
+    move |s: &mut &'s Bytes| {
+        seq! { Tab {
+            _ : take(4usize),
+            id: le_i32.context(StrContext::Label("id")),
+            index: le_i32.context(StrContext::Label("index")),
+
+            url: utf8.context(StrContext::Label("url")),
+
+            title: utf16.context(StrContext::Label("title")),
+
+
+            state: le_u32.flat_map(|len| {
+                take_padded(len).and_then(take(len).map(|s: &[u8]| s.to_vec()))
+            }).context(StrContext::Label("state")),
+
+            transition: le_u32.context(StrContext::Label("transition")).map(PageTransition),
+            post: or_default(le_i32.context(StrContext::Label("post")).map(|v| v != 0)),
+
+            referrer_url: or_default(utf8.context(StrContext::Label("referrer_url"))),
+
+            reference_policy: or_default(le_i32.context(StrContext::Label("reference_policy"))),
+
+            original_request_url: or_default(
+                utf8.context(StrContext::Label("original_request_url"))
+            ),
+
+            user_agent: or_default(le_i32.context(StrContext::Label("user_agent")).map(|v| v != 0)),
+            extra: rest.map(|s: &[u8]| s.to_vec()),
+        }}
+        .parse_next(s)
+    }
+}
+
+/// `parser`, or the default value if there is nothing left to parse
+fn or_default<'s, O: Default>(
+    mut parser: impl Parser<&'s Bytes, O, ContextError>,
+) -> impl Parser<&'s Bytes, O, ContextError> {
+    move |s: &mut &'s Bytes| match s.is_empty() {
+        true => Ok(O::default()),
+        false => parser.parse_next(s),
+    }
+}
+
+fn parse_tab_window<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::TabWindow {
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_tab_index_in_window<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::TabIndexInWindow {
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        index: le_i32.context(StrContext::Label("index")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_selected_navigation_index<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::SelectedNavigationIndex {
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        index: le_i32.context(StrContext::Label("index")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_selected_tab_in_index<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::SelectedTabInIndex {
+        window_id: le_i32.context(StrContext::Label("window_id")),
         index: le_i32.context(StrContext::Label("index")),
+    }}
+    .parse_next(s)
+}
 
-        url: le_u32.flat_map(|len|
-            take(len.next_multiple_of(4)).and_then(take(len).try_map(|s: &[u8]| String::from_utf8(s.to_vec())))
-        ).context(StrContext::Label("url")),
+fn parse_window_type<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::WindowType {
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        window_type: le_i32.context(StrContext::Label("window_type")),
+    }}
+    .parse_next(s)
+}
 
-        // UTF-16 encoding
-        title: le_u32.map(|clen| clen * 2).flat_map(|len|
-            take(len.next_multiple_of(4)).and_then(take(len).try_map(|s: &[u8]| {
-                let buf: Vec<u16> = s
-                    .chunks_exact(2)
-                    .map(|chunk| u16::from_le_bytes(chunk.try_into().unwrap()))
-                    .collect();
-                String::from_utf16(&buf)
-            }))
-        ).context(StrContext::Label("title")),
+fn parse_pinned_state<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::PinnedState {
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        pinned: le_i32.context(StrContext::Label("pinned")).map(|v| v != 0),
+    }}
+    .parse_next(s)
+}
+
+fn parse_extension_app_id<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::ExtensionAppId {
+        // pickle header
+        _: take(4usize),
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        extension_app_id: parse_padded_utf8.context(StrContext::Label("extension_app_id")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_window_bounds<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::WindowBounds {
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        x: le_i32.context(StrContext::Label("x")),
+        y: le_i32.context(StrContext::Label("y")),
+        width: le_i32.context(StrContext::Label("width")),
+        height: le_i32.context(StrContext::Label("height")),
+        show_state: le_i32.context(StrContext::Label("show_state")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_window_app_name<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::WindowAppName {
+        // pickle header
+        _: take(4usize),
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        app_name: parse_padded_utf8.context(StrContext::Label("app_name")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_window_workspace<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::WindowWorkspace {
+        // pickle header
+        _: take(4usize),
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        workspace: parse_padded_utf8.context(StrContext::Label("workspace")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_window_user_title<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::WindowUserTitle {
+        // pickle header
+        _: take(4usize),
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        title: parse_padded_utf8.context(StrContext::Label("title")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_tab_closed<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::TabClosed {
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        // padding for the 8-bytes alignment of the timestamp
+        _: take(4usize).context(StrContext::Label("close_time")),
+        close_time: le_i64.context(StrContext::Label("close_time")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_window_closed<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::WindowClosed {
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        // padding for the 8-bytes alignment of the timestamp
+        _: take(4usize).context(StrContext::Label("close_time")),
+        close_time: le_i64.context(StrContext::Label("close_time")),
+    }}
+    .parse_next(s)
+}
+
+fn parse_tab_user_agent_override<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::TabUserAgentOverride {
+        // pickle header
+        _: take(4usize),
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        user_agent: parse_padded_utf8.context(StrContext::Label("user_agent")),
+        // only kCommandSetTabUserAgentOverride2 has client hints
+        client_hints: rest.map(|s: &[u8]| (!s.is_empty()).then(|| s.to_vec())),
+    }}
+    .parse_next(s)
+}
+
+fn parse_active_window<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::ActiveWindow {
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        _: eof.context(StrContext::Expected(StrContextValue::Description("a 4 bytes payload"))),
+    }}
+    .parse_next(s)
+}
 
+fn parse_last_active_time<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::LastActiveTime {
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        // padding for the 8-bytes alignment of the timestamp
+        _: take(4usize).context(StrContext::Label("last_active_time")),
+        last_active_time: le_i64.context(StrContext::Label("last_active_time")),
+    }}
+    .parse_next(s)
+}
 
-        state: le_u32.flat_map(|len| {
-            take(len.next_multiple_of(4)).and_then(take(len).map(|s: &[u8]| s.to_vec()))
-        }).context(StrContext::Label("state")),
+fn parse_navigation_path_pruned<'s>(
+    from_front: bool,
+) -> impl Parser<&'s Bytes, Content<'s>, ContextError> {
+    move |s: &mut &'s Bytes| {
+        seq! { Content::NavigationPathPruned {
+            tab_id: le_i32.context(StrContext::Label("tab_id")),
+            count: le_i32.context(StrContext::Label("count")),
+            from_front: empty.value(from_front),
+        }}
+        .parse_next(s)
+    }
+}
 
-        transition: le_u32.context(StrContext::Label("transition")).map(PageTransition),
-        post: le_i32.context(StrContext::Label("post")).map(|v| v != 0),
+fn parse_tab_navigation_path_pruned<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::TabNavigationPathPruned {
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        index: le_i32.context(StrContext::Label("index")),
+        count: le_i32.context(StrContext::Label("count")),
+    }}
+    .parse_next(s)
+}
 
-        referrer_url: le_u32.flat_map(|len| {
-            take(len.next_multiple_of(4)).and_then(take(len).try_map(|s: &[u8]| String::from_utf8(s.to_vec())))
-        }).context(StrContext::Label("referrer_url")),
+fn parse_window_visible_on_all_workspaces<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::WindowVisibleOnAllWorkspaces {
+        window_id: le_i32.context(StrContext::Label("window_id")),
+        visible_on_all_workspaces: le_i32.context(StrContext::Label("visible_on_all_workspaces")).map(|v| v != 0),
+    }}
+    .parse_next(s)
+}
 
-        reference_policy: le_i32.context(StrContext::Label("reference_policy")),
+fn parse_tab_group<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::TabGroup {
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        // padding for the 8-bytes alignment of the token
+        _: take(4usize).context(StrContext::Label("group")),
+        group: (parse_token, le_u8)
+            .map(|(token, has_group)| (has_group != 0).then_some(token))
+            .context(StrContext::Label("group")),
+        // struct padding
+        _: rest,
+    }}
+    .parse_next(s)
+}
 
-        original_request_url: le_u32.flat_map(|len| {
-            take(len.next_multiple_of(4)).and_then(take(len).try_map(|s: &[u8]| String::from_utf8(s.to_vec())))
-        }).context(StrContext::Label("original_request_url")),
+/// Tab group metadata whose title is decoded with [parse_padded_utf16_lossy] if `lossy`
+fn parse_tab_group_metadata<'s>(lossy: bool) -> impl Parser<&'s Bytes, Content<'s>, ContextError> {
+    let utf16 = move |s: &mut &'s Bytes| match lossy {
+        true => parse_padded_utf16_lossy(s),
+        false => parse_padded_utf16(s),
+    };
+    move |s: &mut &'s Bytes| {
+        seq! { Content::TabGroupMetadata {
+            // pickle header
+            _: take(4usize),
+            group: parse_token.context(StrContext::Label("group")),
+            title: utf16.context(StrContext::Label("title")),
+            color: le_i32.context(StrContext::Label("color")),
+            collapsed: le_i32.context(StrContext::Label("collapsed")).map(|v| v != 0),
+            // optional saved group guid
+            _: rest,
+        }}
+        .parse_next(s)
+    }
+}
 
-        user_agent: le_i32.context(StrContext::Label("user_agent")).map(|v| v != 0),
-        _: rest
+fn parse_tab_guid<'s>(s: &mut &'s Bytes) -> winnow::Result<Content<'s>> {
+    seq! { Content::TabGuid {
+        // pickle header
+        _: take(4usize),
+        tab_id: le_i32.context(StrContext::Label("tab_id")),
+        guid: parse_padded_utf8.verify(|guid: &str| is_guid(guid)).context(StrContext::Label("guid")),
     }}
     .parse_next(s)
 }
 
+/// Whether `guid` is a hyphenated GUID (8-4-4-4-12 hex digits)
+fn is_guid(guid: &str) -> bool {
+    guid.len() == 36
+        && guid.bytes().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == b'-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// `base::Token`, as its high then low 64-bits halves
+fn parse_token(s: &mut &Bytes) -> winnow::Result<[u8; 16]> {
+    take(16usize)
+        .map(|token: &[u8]| token.try_into().unwrap())
+        .parse_next(s)
+}
+
+/// Length-prefixed UTF-8 string, padded to a 4-bytes boundary, borrowed from the input
+fn parse_padded_utf8<'s>(s: &mut &'s Bytes) -> winnow::Result<Cow<'s, str>> {
+    le_u32
+        .flat_map(|len| {
+            take_padded(len).and_then(
+                take(len)
+                    .try_map(|s: &'s [u8]| core::str::from_utf8(s).map(Cow::Borrowed))
+                    .context(INVALID_UTF8),
+            )
+        })
+        .parse_next(s)
+}
+
+/// Like [parse_padded_utf8], replacing invalid UTF-8 with U+FFFD (only copied in that case)
+fn parse_padded_utf8_lossy<'s>(s: &mut &'s Bytes) -> winnow::Result<Cow<'s, str>> {
+    le_u32
+        .flat_map(|len| take_padded(len).and_then(take(len).map(String::from_utf8_lossy)))
+        .parse_next(s)
+}
+
+/// Length-prefixed (in code units) UTF-16 string, padded to a 4-bytes boundary
+///
+/// A leading byte order mark is dropped.
+fn parse_padded_utf16(s: &mut &Bytes) -> winnow::Result<String> {
+    le_u32
+        .verify_map(|clen: u32| clen.checked_mul(2))
+        .context(INVALID_UTF16)
+        .flat_map(|len| {
+            take_padded(len).and_then(
+                take(len)
+                    .map(utf16_units)
+                    .try_map(|buf| String::from_utf16(&buf))
+                    .context(INVALID_UTF16),
+            )
+        })
+        .parse_next(s)
+}
+
+/// Like [parse_padded_utf16], but lone surrogates are replaced with U+FFFD
+fn parse_padded_utf16_lossy(s: &mut &Bytes) -> winnow::Result<String> {
+    le_u32
+        .verify_map(|clen: u32| clen.checked_mul(2))
+        .context(INVALID_UTF16)
+        .flat_map(|len| {
+            take_padded(len).and_then(
+                take(len)
+                    .map(utf16_units)
+                    .map(|buf| String::from_utf16_lossy(&buf)),
+            )
+        })
+        .parse_next(s)
+}
+
+/// Little-endian code units of `s`, without a leading byte order mark
+fn utf16_units(s: &[u8]) -> Vec<u16> {
+    let mut buf: Vec<u16> = s
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect();
+    if buf.first() == Some(&0xFEFF) {
+        buf.remove(0);
+    }
+    buf
+}
+
+/// `len` bytes with their padding to a 4-bytes boundary
+///
+/// A `len` past the end of the input fails with [LengthExceeded] before computing the padding.
+fn take_padded<'s>(len: u32) -> impl Parser<&'s Bytes, &'s [u8], ContextError> {
+    move |s: &mut &'s Bytes| {
+        let len = len as usize;
+        if len > s.len() {
+            let remaining = s.len();
+            return Err(ContextError::from_external_error(
+                s,
+                LengthExceeded { len, remaining },
+            ));
+        }
+        take(len.next_multiple_of(4)).parse_next(s)
+    }
+}
+
+/// Cause of a length prefix running past the end of its command
+#[derive(Debug)]
+struct LengthExceeded {
+    len: usize,
+    remaining: usize,
+}
+
+impl Display for LengthExceeded {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let LengthExceeded { len, remaining } = self;
+        write!(f, "length {len} exceeds remaining {remaining} bytes")
+    }
+}
+
+impl core::error::Error for LengthExceeded {}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,227 @@
+//! Decoding of the serialized `blink::PageState` stored in [Tab::state](crate::Tab::state)
+//!
+//! Since version 26, the state is a pickle holding the version followed by a mojo message
+//! (see `third_party/blink/common/page_state/page_state_serialization.cc`). Older versions are not
+//! supported.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use winnow::{
+    Bytes, Parser,
+    binary::{le_i32, le_u32},
+    combinator::seq,
+    error::StrContext,
+    token::take,
+};
+
+use crate::{Error, ErrorKind};
+
+/// Oldest version serialized as a mojo message
+const MIN_VERSION: i32 = 26;
+
+/// Top-level frame of a `blink::PageState`
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageState {
+    /// Version of the serialization
+    pub version: i32,
+    pub url: Option<String>,
+    pub referrer: Option<String>,
+    /// Name of the frame targeted by the navigation
+    pub target: Option<String>,
+    /// Form control states, as serialized by Blink
+    pub document_state: Vec<String>,
+    pub view_state: Option<ViewState>,
+}
+
+/// Viewport of the page when it was left
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViewState {
+    /// Scroll position of the visual viewport (eg. when pinch-zoomed)
+    pub visual_viewport_scroll_offset: (f32, f32),
+    /// Scroll position of the page
+    pub scroll_offset: (i32, i32),
+    pub page_scale_factor: f64,
+}
+
+impl PageState {
+    /// Decode a serialized page state, eg. a [Tab::state](crate::Tab::state) kept apart from its
+    /// tab (see [Tab::page_state](crate::Tab::page_state))
+    pub fn parse(state: &[u8]) -> Result<PageState, Error> {
+        let mut input = Bytes::new(state);
+        let (version, message) = seq!(
+            _: take(4usize).context(StrContext::Label("pickle header")),
+            le_i32.context(StrContext::Label("version")),
+            le_u32.flat_map(take).context(StrContext::Label("message")),
+        )
+        .parse_next(&mut input)
+        .map_err(|err| Error::from_context(state.len() - input.len(), err))?;
+
+        if version < MIN_VERSION {
+            return Err(Error {
+                kind: ErrorKind::Other,
+                message: format!("unsupported page state version {version}"),
+                offset: 4,
+                context: vec!["version"],
+                command_index: None,
+            });
+        }
+
+        // pickle header, version and message length
+        let message = Message {
+            data: message,
+            base: 12,
+        };
+
+        let top = message
+            .pointer(16, "top")?
+            .ok_or_else(|| message.error(16, "top", "null"))?;
+        let top = top + 8;
+
+        let document_state = match message.pointer(top + 32, "document_state")? {
+            Some(array) => message
+                .pointers(array, "document_state")?
+                .map(|at| Ok(message.string16(at, "document_state")?.unwrap_or_default()))
+                .collect::<Result<_, Error>>()?,
+            None => Vec::new(),
+        };
+
+        let view_state = match message.pointer(top + 48, "view_state")? {
+            Some(view_state) => {
+                let view_state = view_state + 8;
+                let visual = message.pointer(view_state, "visual_viewport_scroll_offset")?;
+                let scroll = message.pointer(view_state + 8, "scroll_offset")?;
+                Some(ViewState {
+                    visual_viewport_scroll_offset: match visual {
+                        Some(at) => (
+                            f32::from_bits(message.u32(at + 8, "visual_viewport_scroll_offset")?),
+                            f32::from_bits(message.u32(at + 12, "visual_viewport_scroll_offset")?),
+                        ),
+                        None => (0.0, 0.0),
+                    },
+                    scroll_offset: match scroll {
+                        Some(at) => (
+                            message.u32(at + 8, "scroll_offset")? as i32,
+                            message.u32(at + 12, "scroll_offset")? as i32,
+                        ),
+                        None => (0, 0),
+                    },
+                    page_scale_factor: f64::from_bits(
+                        message.u64(view_state + 16, "page_scale_factor")?,
+                    ),
+                })
+            }
+            None => None,
+        };
+
+        Ok(PageState {
+            version,
+            url: message.string16(top, "url")?,
+            referrer: message.string16(top + 8, "referrer")?,
+            target: message.string16(top + 16, "target")?,
+            document_state,
+            view_state,
+        })
+    }
+
+    /// Scroll position of the page, see [ViewState::scroll_offset]
+    pub fn scroll_offset(&self) -> Option<(i32, i32)> {
+        self.view_state.map(|view_state| view_state.scroll_offset)
+    }
+}
+
+/// Mojo message, addressed by offsets from its start
+struct Message<'a> {
+    data: &'a [u8],
+    /// Offset of the message within the page state, for error reporting
+    base: usize,
+}
+
+impl Message<'_> {
+    fn error(&self, at: usize, label: &'static str, message: &str) -> Error {
+        Error {
+            kind: ErrorKind::Other,
+            message: message.to_string(),
+            offset: self.base + at,
+            context: vec![label],
+            command_index: None,
+        }
+    }
+
+    fn out_of_bounds(&self, at: usize, label: &'static str) -> Error {
+        Error {
+            kind: ErrorKind::UnexpectedEof,
+            ..self.error(at, label, "out of bounds")
+        }
+    }
+
+    fn bytes<const N: usize>(&self, at: usize, label: &'static str) -> Result<[u8; N], Error> {
+        at.checked_add(N)
+            .and_then(|end| self.data.get(at..end))
+            .map(|bytes| bytes.try_into().unwrap())
+            .ok_or_else(|| self.out_of_bounds(at, label))
+    }
+
+    fn u32(&self, at: usize, label: &'static str) -> Result<u32, Error> {
+        self.bytes(at, label).map(u32::from_le_bytes)
+    }
+
+    fn u64(&self, at: usize, label: &'static str) -> Result<u64, Error> {
+        self.bytes(at, label).map(u64::from_le_bytes)
+    }
+
+    /// Resolve the relative pointer stored at `at`
+    fn pointer(&self, at: usize, label: &'static str) -> Result<Option<usize>, Error> {
+        match self.u64(at, label)? {
+            0 => Ok(None),
+            relative => usize::try_from(relative)
+                .ok()
+                .and_then(|relative| at.checked_add(relative))
+                .filter(|&target| target < self.data.len())
+                .map(Some)
+                .ok_or_else(|| self.error(at, label, "invalid pointer")),
+        }
+    }
+
+    /// Locations of the elements of the array of pointers at `at`
+    fn pointers(
+        &self,
+        at: usize,
+        label: &'static str,
+    ) -> Result<impl Iterator<Item = usize>, Error> {
+        let len = self.u32(at + 4, label)? as usize;
+        let end = len
+            .checked_mul(8)
+            .and_then(|size| (at + 8).checked_add(size))
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| self.out_of_bounds(at, label))?;
+        Ok((at + 8..end).step_by(8))
+    }
+
+    /// Nullable `mojo_base.mojom.String16` pointed at by `at`
+    fn string16(&self, at: usize, label: &'static str) -> Result<Option<String>, Error> {
+        let Some(string) = self.pointer(at, label)? else {
+            return Ok(None);
+        };
+        let array = self
+            .pointer(string + 8, label)?
+            .ok_or_else(|| self.error(string + 8, label, "null"))?;
+        let len = self.u32(array + 4, label)? as usize;
+        let buf = len
+            .checked_mul(2)
+            .and_then(|size| (array + 8).checked_add(size))
+            .and_then(|end| self.data.get(array + 8..end))
+            .ok_or_else(|| self.out_of_bounds(array, label))?;
+        let buf: Vec<u16> = buf
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+        Ok(Some(String::from_utf16_lossy(&buf)))
+    }
+}
@@ -10,12 +10,29 @@ fn test_parse() {
     let [cmd1, cmd2, cmd3] = snss.commands.try_into().unwrap();
 
     assert_eq!(cmd1.id, 14);
-    let Content::Other(c1) = cmd1.content else {
+    assert_eq!(cmd1.command_id(), CommandId::SetWindowBounds3);
+    let Content::WindowBounds {
+        window_id,
+        x,
+        y,
+        width,
+        height,
+        show_state,
+    } = cmd1.content
+    else {
         panic!()
     };
-    assert_eq!(c1.len(), 24);
+    assert_eq!(window_id, 1994883224);
+    assert_eq!((x, y, width, height), (6, 38, 1524, 759));
+    assert_eq!(show_state, 1);
+    assert_eq!(
+        WindowShowState::as_known(show_state),
+        Ok(WindowShowState::Normal)
+    );
+    assert_eq!(WindowShowState::as_known(6), Err(6));
 
     assert_eq!(cmd2.id, 6);
+    assert_eq!(cmd2.command_id(), CommandId::UpdateTabNavigation);
     let Content::Tab(c2) = cmd2.content else {
         panic!()
     };
@@ -41,11 +58,11 @@ fn test_parse() {
         PageTransitionQualifiers {
             back_forward: false,
             address_bar: false,
-            homepage: true,
-            chain_start: true,
-            redirect_chain_end: true,
-            client_redirect: true,
-            server_redirect: true,
+            homepage: false,
+            chain_start: false,
+            redirect_chain_end: false,
+            client_redirect: false,
+            server_redirect: false,
         }
     );
 
@@ -75,11 +92,2056 @@ fn test_parse() {
         PageTransitionQualifiers {
             back_forward: false,
             address_bar: false,
+            homepage: false,
+            chain_start: false,
+            redirect_chain_end: false,
+            client_redirect: false,
+            server_redirect: false,
+        }
+    );
+}
+
+#[test]
+fn test_borrowed_strings() {
+    let data = include_bytes!("Session");
+    let snss = parse(data.as_slice()).unwrap();
+
+    let tab = snss.tabs().next().unwrap();
+    assert!(matches!(tab.url, Cow::Borrowed(_)));
+    assert!(matches!(tab.referrer_url, Cow::Borrowed(_)));
+    assert!(matches!(tab.original_request_url, Cow::Borrowed(_)));
+
+    let owned: SNSS<'static> = snss.into_owned();
+    let tab = owned.tabs().next().unwrap();
+    assert!(matches!(tab.url, Cow::Owned(_)));
+    assert_eq!(
+        tab.url,
+        "https://console.hetzner.cloud/projects/3687808/servers/64199561/graphs"
+    );
+}
+
+#[test]
+fn test_borrowed_command_strings() {
+    let app_name = pickle(&[i32s(&[1]), padded_str("app")].concat());
+    let user_agent = pickle(&[i32s(&[2]), padded_str("Mozilla/5.0")].concat());
+    let data = snss_bytes(&[(15, &app_name), (18, &user_agent)]);
+    let snss = parse(&data).unwrap();
+
+    let [app_name, user_agent] = snss.commands.try_into().unwrap();
+    let Content::WindowAppName { app_name, .. } = app_name.content else {
+        panic!()
+    };
+    assert!(matches!(app_name, Cow::Borrowed("app")));
+    let Content::TabUserAgentOverride { user_agent, .. } = user_agent.content else {
+        panic!()
+    };
+    assert!(matches!(user_agent, Cow::Borrowed("Mozilla/5.0")));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parse_from_reader() {
+    let data = include_bytes!("Session");
+
+    let snss = parse_from_reader(std::io::Cursor::new(data)).unwrap();
+    assert_eq!(snss.version, 3);
+    assert_eq!(snss.commands.len(), 3);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_parse_maybe_compressed() {
+    use std::io::Write;
+
+    let data = include_bytes!("Session");
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let expected = format!("{:?}", parse(data).unwrap().commands);
+    let snss = parse_maybe_compressed(&compressed).unwrap();
+    assert_eq!(format!("{:?}", snss.commands), expected);
+    let snss = parse_maybe_compressed(data).unwrap();
+    assert_eq!(format!("{:?}", snss.commands), expected);
+
+    let err = parse_maybe_compressed(&compressed[..20]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parse_reader() {
+    /// Reader returning at most 3 bytes per read
+    struct Trickle<'a>(&'a [u8]);
+
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    let data = include_bytes!("Session");
+    let snss = parse_reader(std::io::Cursor::new(data)).unwrap();
+    assert_eq!(
+        format!("{:?}", snss.commands),
+        format!("{:?}", parse(data).unwrap().commands)
+    );
+    let snss = parse_reader(Trickle(data)).unwrap();
+    assert_eq!(snss.version, 3);
+    assert_eq!(snss.commands.len(), 3);
+
+    let truncated = &data[..data.len() - 1];
+    let err = parse_reader(Trickle(truncated)).unwrap_err();
+    let expected = parse(truncated).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TruncatedCommand);
+    assert_eq!(err.command_index(), Some(2));
+    assert_eq!(err.offset(), expected.offset());
+
+    // at the field that failed, like parse
+    let mut invalid = data.to_vec();
+    invalid[0x36] = 0xFF;
+    let err = parse_reader(Trickle(&invalid)).unwrap_err();
+    assert_eq!(err.offset(), 0x36);
+
+    let err = parse_reader(Trickle(b"SNSS")).unwrap_err();
+    assert_eq!(err.context(), ["version"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parse_file() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/Session");
+
+    let snss = parse_file(path).unwrap();
+    assert_eq!(snss.version, 3);
+    assert_eq!(snss.commands.len(), 3);
+
+    let err = parse_file("does/not/exist").unwrap_err();
+    assert_eq!(err.offset, 0);
+    assert!(err.message.starts_with("failed to read does/not/exist"));
+}
+
+/// Builds a version 3 SNSS file out of `(id, payload)` commands
+fn snss_bytes(commands: &[(u8, &[u8])]) -> Vec<u8> {
+    let mut data = b"SNSS".to_vec();
+    data.extend_from_slice(&3i32.to_le_bytes());
+    for (id, payload) in commands {
+        data.extend_from_slice(&(payload.len() as u16 + 1).to_le_bytes());
+        data.push(*id);
+        data.extend_from_slice(payload);
+    }
+    data
+}
+
+/// Concatenates little-endian `i32`s into a command payload
+fn i32s(values: &[i32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Length-prefixed UTF-8 string padded to 4 bytes, as written by Chromium's `base::Pickle`
+fn padded_str(value: &str) -> Vec<u8> {
+    let mut bytes = (value.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(value.as_bytes());
+    bytes.resize(4 + value.len().next_multiple_of(4), 0);
+    bytes
+}
+
+/// Builds a `kCommandUpdateTabNavigation` payload with only an url
+fn tab_payload(id: i32, index: i32, url: &str) -> Vec<u8> {
+    let mut payload = i32s(&[id, index]);
+    payload.extend_from_slice(&padded_str(url));
+    // title, state, transition, post
+    payload.extend_from_slice(&i32s(&[0, 0, 0, 0]));
+    payload.extend_from_slice(&padded_str(""));
+    // reference_policy
+    payload.extend_from_slice(&i32s(&[0]));
+    payload.extend_from_slice(&padded_str(""));
+    // user_agent
+    payload.extend_from_slice(&i32s(&[0]));
+    pickle(&payload)
+}
+
+/// Prepends the `base::Pickle` header (payload size) to `payload`
+fn pickle(payload: &[u8]) -> Vec<u8> {
+    let mut bytes = (payload.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+#[test]
+fn test_parse_window_commands() {
+    let data = snss_bytes(&[(24, &i32s(&[7, 2, 3])), (32, &i32s(&[4, 1]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd1, cmd2] = snss.commands.try_into().unwrap();
+
+    let Content::TabNavigationPathPruned {
+        tab_id,
+        index,
+        count,
+    } = cmd1.content
+    else {
+        panic!()
+    };
+    assert_eq!((tab_id, index, count), (7, 2, 3));
+
+    let Content::WindowVisibleOnAllWorkspaces {
+        window_id,
+        visible_on_all_workspaces,
+    } = cmd2.content
+    else {
+        panic!()
+    };
+    assert_eq!(window_id, 4);
+    assert!(visible_on_all_workspaces);
+}
+
+#[test]
+fn test_parse_window_workspace() {
+    let payload = pickle(&[i32s(&[1994883224]), padded_str("desk-2")].concat());
+    let data = snss_bytes(&[(23, &payload), (22, &payload)]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd1, cmd2] = snss.commands.try_into().unwrap();
+    assert_eq!(
+        cmd1.content,
+        Content::WindowWorkspace {
+            window_id: 1994883224,
+            workspace: "desk-2".into(),
+        }
+    );
+    // obsolete kCommandSetWindowWorkspace
+    assert_eq!(cmd2.content, Content::Other(payload));
+}
+
+#[test]
+fn test_parse_navigation_path_pruned() {
+    let data = snss_bytes(&[(5, &i32s(&[7, 2])), (11, &i32s(&[7, 3]))]);
+
+    let snss = parse(&data).unwrap();
+    let contents: Vec<_> = snss.commands.into_iter().map(|cmd| cmd.content).collect();
+    assert_eq!(
+        contents,
+        [
+            Content::NavigationPathPruned {
+                tab_id: 7,
+                count: 2,
+                from_front: false,
+            },
+            Content::NavigationPathPruned {
+                tab_id: 7,
+                count: 3,
+                from_front: true,
+            },
+        ]
+    );
+
+    // truncated
+    let data = snss_bytes(&[(11, &i32s(&[7]))]);
+    assert_eq!(parse(&data).unwrap_err().context(), ["count"]);
+}
+
+#[test]
+fn test_qualifiers() {
+    let transition = PageTransition(0xC6000000);
+    assert_eq!(transition.kind().unwrap(), PageTransitionType::Link);
+    assert_eq!(
+        transition.qualifiers(),
+        PageTransitionQualifiers {
+            back_forward: false,
+            address_bar: true,
             homepage: true,
-            chain_start: true,
-            redirect_chain_end: true,
+            chain_start: false,
+            redirect_chain_end: false,
             client_redirect: true,
             server_redirect: true,
         }
     );
 }
+
+#[test]
+fn test_qualifiers_back_forward_only() {
+    assert_eq!(
+        PageTransition(0x01000000).qualifiers(),
+        PageTransitionQualifiers {
+            back_forward: true,
+            address_bar: false,
+            homepage: false,
+            chain_start: false,
+            redirect_chain_end: false,
+            client_redirect: false,
+            server_redirect: false,
+        }
+    );
+}
+
+#[test]
+fn test_parse_tab_closed() {
+    // 2025-06-22T10:00:00.123456Z
+    let mut payload = i32s(&[42, 0]);
+    payload.extend_from_slice(&13_395_060_000_123_456i64.to_le_bytes());
+    let data = snss_bytes(&[(16, &payload)]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    assert_eq!(cmd.command_id(), CommandId::TabClosed);
+    let Content::TabClosed { tab_id, close_time } = cmd.content else {
+        panic!()
+    };
+    assert_eq!(tab_id, 42);
+    assert_eq!(close_time, 13_395_060_000_123_456);
+}
+
+#[test]
+fn test_parse_tab_window() {
+    let data = snss_bytes(&[(0, &i32s(&[1994883224, 1994883225]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    assert_eq!(cmd.id, 0);
+    let Content::TabWindow { window_id, tab_id } = cmd.content else {
+        panic!()
+    };
+    assert_eq!(window_id, 1994883224);
+    assert_eq!(tab_id, 1994883225);
+}
+
+#[test]
+fn test_parse_tab_index_in_window() {
+    let data = snss_bytes(&[(2, &i32s(&[5, 3]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    let Content::TabIndexInWindow { tab_id, index } = cmd.content else {
+        panic!()
+    };
+    assert_eq!((tab_id, index), (5, 3));
+}
+
+#[test]
+fn test_parse_truncated_tab_index_in_window() {
+    let data = snss_bytes(&[(2, &[5, 0, 0, 0, 3])]);
+
+    let err = parse(&data).unwrap_err();
+    // header, length prefix, id and tab_id
+    assert_eq!(err.offset, 8 + 2 + 1 + 4);
+    assert_eq!(err.message, "invalid index");
+    assert_eq!(err.context, ["index"]);
+}
+
+#[test]
+fn test_parse_selected_tab_in_index() {
+    let data = snss_bytes(&[(8, &i32s(&[1994883224, 2])), (8, &i32s(&[1994883230, 0]))]);
+
+    let snss = parse(&data).unwrap();
+    let selected: Vec<_> = snss
+        .commands
+        .iter()
+        .map(|cmd| match cmd.content {
+            Content::SelectedTabInIndex { window_id, index } => (cmd.id, window_id, index),
+            _ => panic!(),
+        })
+        .collect();
+    assert_eq!(selected, [(8, 1994883224, 2), (8, 1994883230, 0)]);
+}
+
+#[test]
+fn test_parse_selected_navigation_index() {
+    let data = snss_bytes(&[(7, &i32s(&[1994883225, 1]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    let Content::SelectedNavigationIndex { tab_id, index } = cmd.content else {
+        panic!()
+    };
+    assert_eq!(tab_id, 1994883225);
+    assert_eq!(index, 1);
+}
+
+#[test]
+fn test_tabs() {
+    let data = include_bytes!("Session");
+    let snss = parse(data.as_slice()).unwrap();
+
+    let indices: Vec<_> = snss.tabs().map(|tab| (tab.id, tab.index)).collect();
+    assert_eq!(indices, [(1994883225, 0), (1994883225, 1)]);
+
+    let urls: Vec<_> = snss.into_tabs().map(|tab| tab.url).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://console.hetzner.cloud/projects/3687808/servers/64199561/graphs",
+            "https://console.hetzner.cloud/projects/3687808/servers/64199561/loadbalancers"
+        ]
+    );
+}
+
+#[test]
+fn test_parse_pinned_state() {
+    let data = snss_bytes(&[(12, &i32s(&[3, 1])), (12, &i32s(&[4, 0]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd1, cmd2] = snss.commands.try_into().unwrap();
+
+    let Content::PinnedState { tab_id, pinned } = cmd1.content else {
+        panic!()
+    };
+    assert_eq!(tab_id, 3);
+    assert!(pinned);
+
+    let Content::PinnedState { tab_id, pinned } = cmd2.content else {
+        panic!()
+    };
+    assert_eq!(tab_id, 4);
+    assert!(!pinned);
+}
+
+#[test]
+fn test_parse_malformed_window_bounds() {
+    let data = snss_bytes(&[(14, &i32s(&[1, 2, 3, 4, 5]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    let Content::Other(payload) = cmd.content else {
+        panic!()
+    };
+    assert_eq!(payload.len(), 20);
+}
+
+#[test]
+fn test_parse_other_framing() {
+    let data = snss_bytes(&[(99, &[1, 2, 3]), (98, &[]), (97, &[4, 5])]);
+
+    let snss = parse(&data).unwrap();
+    let payloads: Vec<_> = snss
+        .commands
+        .into_iter()
+        .map(|cmd| match cmd.content {
+            Content::Other(payload) => (cmd.id, payload),
+            _ => panic!(),
+        })
+        .collect();
+    assert_eq!(
+        payloads,
+        [(99, vec![1, 2, 3]), (98, vec![]), (97, vec![4, 5])]
+    );
+}
+
+#[test]
+fn test_parse_largest_frame() {
+    let payload = vec![7; u16::MAX as usize - 1];
+    let mut data = snss_bytes(&[(99, &payload), (98, &[1])]);
+    assert_eq!(data[8..10], [0xFF, 0xFF]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd1, cmd2] = snss.commands.try_into().unwrap();
+    assert!(matches!(cmd1.content, Content::Other(p) if p == payload));
+    assert!(matches!(cmd2.content, Content::Other(p) if p == [1]));
+
+    // a 32-bit size after the marker is only the start of a 65535 bytes frame
+    data.truncate(8);
+    data.extend_from_slice(&[0xFF, 0xFF, 99]);
+    data.extend_from_slice(&70000u32.to_le_bytes());
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TruncatedCommand);
+}
+
+#[test]
+fn test_parse_window_type() {
+    let data = snss_bytes(&[(9, &i32s(&[1994883224, 2])), (9, &i32s(&[1994883224, 42]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd1, cmd2] = snss.commands.try_into().unwrap();
+
+    let Content::WindowType {
+        window_id,
+        window_type,
+    } = cmd1.content
+    else {
+        panic!()
+    };
+    assert_eq!(window_id, 1994883224);
+    assert_eq!(WindowType::as_known(window_type), Ok(WindowType::App));
+
+    let Content::WindowType { window_type, .. } = cmd2.content else {
+        panic!()
+    };
+    assert_eq!(WindowType::as_known(window_type), Err(42));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let data = include_bytes!("Session");
+    let snss = parse(data.as_slice()).unwrap();
+
+    let json = serde_json::to_value(&snss).unwrap();
+    assert_eq!(json["version"], 3);
+    assert_eq!(json["commands"][1]["id"], 6);
+    assert_eq!(
+        json["commands"][1]["content"]["Tab"]["title"],
+        "primary · Hetzner Cloud"
+    );
+    assert!(json["commands"][1]["content"]["Tab"]["state"].is_string());
+    assert_eq!(
+        json["commands"][1]["content"]["Tab"]["transition"],
+        serde_json::json!({
+            "value": 8,
+            "kind": "Reload",
+            "qualifiers": {
+                "back_forward": false,
+                "address_bar": false,
+                "homepage": false,
+                "chain_start": false,
+                "redirect_chain_end": false,
+                "client_redirect": false,
+                "server_redirect": false,
+            },
+        })
+    );
+
+    let snss: SNSS = serde_json::from_value(json).unwrap();
+    let Content::Tab(tab) = &snss.commands[1].content else {
+        panic!()
+    };
+    assert_eq!(tab.state.len(), 1276);
+    assert_eq!(tab.transition.0, 8);
+
+    let json = serde_json::to_value(PageTransition(0x010000FF)).unwrap();
+    assert_eq!(json["kind"], serde_json::Value::Null);
+    assert!(json["qualifiers"]["back_forward"].as_bool().unwrap());
+}
+
+#[test]
+fn test_parse_window_app_name() {
+    let mut payload = i32s(&[1994883224]);
+    payload.extend(padded_str("Café"));
+    let data = snss_bytes(&[(15, &pickle(&payload))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    let Content::WindowAppName {
+        window_id,
+        app_name,
+    } = cmd.content
+    else {
+        panic!()
+    };
+    assert_eq!(window_id, 1994883224);
+    assert_eq!(app_name, "Café");
+}
+
+#[test]
+fn test_command_id() {
+    for id in 0..=u8::MAX {
+        assert_eq!(CommandId::from(id).raw(), id);
+    }
+    assert_eq!(CommandId::from(16), CommandId::TabClosed);
+    assert_eq!(CommandId::from(3), CommandId::Unknown(3));
+    assert_eq!(CommandId::from(200), CommandId::Unknown(200));
+    assert_eq!(
+        CommandId::from(commands::UPDATE_TAB_NAVIGATION),
+        CommandId::UpdateTabNavigation
+    );
+}
+
+#[test]
+fn test_parse_window_closed() {
+    let mut payload = i32s(&[1994883224, 0]);
+    payload.extend_from_slice(&13_395_060_000_123_456i64.to_le_bytes());
+    let data = snss_bytes(&[(17, &payload)]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    let Content::WindowClosed {
+        window_id,
+        close_time,
+    } = cmd.content
+    else {
+        panic!()
+    };
+    assert_eq!(window_id, 1994883224);
+    assert_eq!(close_time, 13_395_060_000_123_456);
+
+    let data = snss_bytes(&[(17, &payload[..12])]);
+    let err = parse(&data).unwrap_err();
+    // header, length prefix, id, window_id and padding
+    assert_eq!(err.offset, 8 + 2 + 1 + 8);
+    assert_eq!(err.message, "invalid close_time");
+    assert_eq!(err.context, ["close_time"]);
+}
+
+// the cause of the error is only kept with std
+#[cfg(feature = "std")]
+#[test]
+fn test_error_context() {
+    let data = include_bytes!("Session");
+    let mut data = data.to_vec();
+    // invalid UTF-8 as the first byte of the first tab's url
+    data[0x36] = 0xFF;
+
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.offset, 0x36);
+    assert_eq!(err.context, ["url"]);
+    assert_eq!(
+        err.message,
+        "invalid utf-8 sequence of 1 bytes from index 0"
+    );
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "error at offset {} in field 'url': invalid utf-8 sequence of 1 bytes from index 0\n",
+            err.offset
+        )
+    );
+}
+
+#[test]
+fn test_parse_tab_user_agent_override() {
+    const UA: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36";
+
+    let mut v1 = i32s(&[7]);
+    v1.extend(padded_str(UA));
+    let mut v2 = v1.clone();
+    // has client hints, followed by the serialized hints
+    v2.extend(i32s(&[1]));
+    v2.extend(padded_str("opaque"));
+    let data = snss_bytes(&[(18, &pickle(&v1)), (29, &pickle(&v2))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd1, cmd2] = snss.commands.try_into().unwrap();
+
+    let hints = [i32s(&[1]), padded_str("opaque")].concat();
+    for (cmd, expected) in [(cmd1, None), (cmd2, Some(hints))] {
+        let Content::TabUserAgentOverride {
+            tab_id,
+            user_agent,
+            client_hints,
+        } = cmd.content
+        else {
+            panic!()
+        };
+        assert_eq!(tab_id, 7);
+        assert_eq!(user_agent, UA);
+        assert_eq!(client_hints, expected);
+    }
+}
+
+#[test]
+fn test_title_lone_surrogate() {
+    let data = include_bytes!("Session");
+    let mut data = data.to_vec();
+    // lone high surrogate as the first character of the first tab's title
+    data[0x82..0x84].copy_from_slice(&0xD800u16.to_le_bytes());
+
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidUtf16 { field: "title" });
+
+    let options = ParseOptions {
+        lossy: true,
+        ..ParseOptions::default()
+    };
+    let snss = parse_with_options(&data, options).unwrap();
+    let titles: Vec<_> = snss.tabs().map(|tab| tab.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        ["\u{FFFD}rimary · Hetzner Cloud", "primary · Hetzner Cloud"]
+    );
+}
+
+#[test]
+fn test_title_bom() {
+    let title: Vec<u8> = [0xFEFF, 'h' as u16, 'i' as u16]
+        .iter()
+        .flat_map(|c| c.to_le_bytes())
+        .collect();
+
+    let mut payload = i32s(&[1, 0]);
+    payload.extend(padded_str("https://example.com/"));
+    payload.extend(i32s(&[3]));
+    payload.extend(&title);
+    payload.extend([0, 0]);
+    // state, transition, post, referrer, policy, original url, user agent
+    payload.extend(i32s(&[0, 0, 0, 0, 0, 0, 0]));
+    let data = snss_bytes(&[(6, &pickle(&payload))]);
+
+    let snss = parse(&data).unwrap();
+    assert_eq!(snss.tabs().next().unwrap().title, "hi");
+}
+
+#[test]
+fn test_string_length_exceeds_command() {
+    let mut payload = i32s(&[1, 0]);
+    payload.extend(i32s(&[4_000_000_000u32 as i32]));
+    payload.extend(padded_str("https://example.com/"));
+    let data = snss_bytes(&[(6, &pickle(&payload))]);
+
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(err.context(), ["url"]);
+    #[cfg(feature = "std")]
+    assert_eq!(
+        err.message(),
+        "length 4000000000 exceeds remaining 24 bytes"
+    );
+
+    // a title running past the end isn't mistaken for invalid UTF-16
+    let mut payload = i32s(&[1, 0]);
+    payload.extend(padded_str("https://example.com/"));
+    payload.extend(i32s(&[64]));
+    let data = snss_bytes(&[(6, &pickle(&payload))]);
+    assert_eq!(parse(&data).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_parse_tab_older_layout() {
+    let mut payload = i32s(&[1, 0]);
+    payload.extend(padded_str("https://example.com/"));
+    // title, state, transition
+    payload.extend(i32s(&[0, 0, 0x01000000]));
+    let mut with_referrer = payload.clone();
+    with_referrer.extend(i32s(&[1]));
+    with_referrer.extend(padded_str("https://referrer.example/"));
+
+    for version in [1, 3] {
+        let mut data = snss_bytes(&[(6, &pickle(&payload)), (6, &pickle(&with_referrer))]);
+        data[4..8].copy_from_slice(&i32::to_le_bytes(version));
+
+        let snss = parse(&data).unwrap();
+        let [tab1, tab2] = snss.tabs().collect::<Vec<_>>().try_into().unwrap();
+        assert_eq!(tab1.url, "https://example.com/");
+        assert_eq!(tab1.transition.kind(), Ok(PageTransitionType::Link));
+        assert!(!tab1.post);
+        assert_eq!(tab1.referrer_url, "");
+        assert!(tab2.post);
+        assert_eq!(tab2.referrer_url, "https://referrer.example/");
+        assert_eq!(tab2.original_request_url, "");
+        assert!(!tab2.user_agent);
+    }
+
+    // a field cut in the middle is still an error
+    payload.extend([1, 0]);
+    let data = snss_bytes(&[(6, &pickle(&payload))]);
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(err.context(), ["post"]);
+}
+
+#[test]
+fn test_to_bytes_older_layout_too_large() {
+    // fits without the fields after its transition, but not once they are written back
+    let mut payload = i32s(&[1, 0]);
+    payload.extend(padded_str(&"a".repeat(65504)));
+    payload.extend(i32s(&[0, 0, 0]));
+    let data = snss_bytes(&[(0, &i32s(&[1, 2])), (6, &pickle(&payload))]);
+    let snss = parse(&data).unwrap();
+
+    let err = snss.to_bytes().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    assert_eq!(err.command_index(), Some(1));
+    assert_eq!(err.offset(), 19);
+}
+
+#[test]
+fn test_title_odd_length() {
+    let mut payload = i32s(&[1, 0]);
+    payload.extend(padded_str("https://example.com/"));
+    // 2 * 0x8000_0001 code units wraps around to 2 bytes
+    payload.extend(i32s(&[0x8000_0001u32 as i32]));
+    payload.extend(b"hi\0\0");
+    payload.extend(i32s(&[0, 0, 0, 0, 0, 0, 0]));
+    let data = snss_bytes(&[(6, &pickle(&payload))]);
+
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidUtf16 { field: "title" });
+    assert_eq!(err.command_index(), Some(0));
+}
+
+#[test]
+fn test_parse_lenient() {
+    let data = snss_bytes(&[
+        (0, &i32s(&[1, 2])),
+        (2, &[5, 0, 0, 0, 3]),
+        (8, &i32s(&[1, 0])),
+    ]);
+    assert!(parse(&data).is_err());
+
+    let (snss, errors) = parse_lenient(&data);
+    assert_eq!(snss.version, 3);
+    let ids: Vec<_> = snss.commands.iter().map(|cmd| cmd.id).collect();
+    assert_eq!(ids, [0, 8]);
+
+    let [err] = errors.try_into().unwrap();
+    // length prefix, id and tab_id of the second command
+    assert_eq!(err.offset, 19 + 2 + 1 + 4);
+    assert_eq!(err.context, ["index"]);
+}
+
+#[test]
+fn test_parse_lenient_session() {
+    let data = include_bytes!("Session");
+    let mut data = data.to_vec();
+    // invalid UTF-8 as the first byte of the first tab's url
+    data[0x36] = 0xFF;
+
+    let (snss, errors) = parse_lenient(&data);
+    let ids: Vec<_> = snss.commands.iter().map(|cmd| cmd.id).collect();
+    assert_eq!(ids, [14, 6]);
+    assert_eq!(snss.tabs().next().unwrap().index, 1);
+
+    let [err] = errors.try_into().unwrap();
+    assert_eq!(err.context, ["url"]);
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn test_write_tabs_csv() {
+    let mut payload = i32s(&[7, 0]);
+    payload.extend(padded_str("https://example.com/"));
+    payload.extend(i32s(&[6]));
+    payload.extend("a, \"b\"".encode_utf16().flat_map(u16::to_le_bytes));
+    // state, transition
+    payload.extend(i32s(&[0, 8]));
+    let data = snss_bytes(&[(6, &pickle(&payload)), (12, &i32s(&[7, 1]))]);
+    let snss = parse(&data).unwrap();
+
+    let mut csv = Vec::new();
+    snss.write_tabs_csv(&mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "id,index,url,title,transition,pinned\n\
+         7,0,https://example.com/,\"a, \"\"b\"\"\",reload,true\n"
+    );
+}
+
+#[cfg(feature = "url")]
+#[test]
+fn test_clean_url() {
+    let clean_url = |url: &str| {
+        Tab {
+            url: url.into(),
+            ..Tab::default()
+        }
+        .clean_url()
+    };
+
+    assert_eq!(
+        clean_url("https://example.com/a?utm_source=x&id=1&fbclid=y&utm_medium=z#top"),
+        "https://example.com/a?id=1#top"
+    );
+    assert_eq!(
+        clean_url("https://example.com/?gclid=1&utm_campaign=2"),
+        "https://example.com/"
+    );
+    assert_eq!(
+        clean_url("https://example.com/?q=a+b"),
+        "https://example.com/?q=a+b"
+    );
+    assert_eq!(
+        clean_url("https://example.com/?q=a%20b&utm_source=x"),
+        "https://example.com/?q=a%20b"
+    );
+    // nothing to clean
+    for url in [
+        "https://example.com/?flag",
+        "https://example.com/?q=a%20b",
+        "https://example.com/?a=1;b=2",
+        "https://example.com",
+    ] {
+        assert_eq!(clean_url(url), url);
+    }
+    assert_eq!(clean_url("chrome://newtab/"), "chrome://newtab/");
+    assert_eq!(clean_url("not a url"), "not a url");
+}
+
+#[test]
+fn test_effective_commands() {
+    let data = snss_bytes(&[(24, &i32s(&[7, 2, 3])), (255, &[]), (12, &i32s(&[7, 1]))]);
+    let snss = parse(&data).unwrap();
+    assert_eq!(snss.commands.len(), 3);
+
+    let ids: Vec<_> = snss.effective_commands().map(|cmd| cmd.id).collect();
+    assert_eq!(ids, [24, 12]);
+
+    // written by both services
+    let data = snss_bytes(&[(4, &[]), (255, &[])]);
+    assert_eq!(parse(&data).unwrap().kind(), FileKind::Tabs);
+}
+
+#[test]
+fn test_version_info() {
+    let data = include_bytes!("Session");
+    let snss = parse(data).unwrap();
+    assert_eq!(snss.version_info(), VersionInfo::WithMarker);
+
+    let mut data = data.to_vec();
+    data[4] = 1;
+    assert_eq!(parse(&data).unwrap().version_info(), VersionInfo::Version1);
+
+    assert_eq!(
+        VersionInfo::from_version(4),
+        VersionInfo::Encrypted { with_marker: true }
+    );
+    assert_eq!(VersionInfo::from_version(9), VersionInfo::Unknown(9));
+    for version in -1..6 {
+        assert_eq!(
+            VersionInfo::from_version(version).is_supported(),
+            SUPPORTED_VERSIONS.contains(&version)
+        );
+    }
+}
+
+#[test]
+fn test_parse_partial() {
+    let data = include_bytes!("Session");
+
+    let (snss, rest) = parse_partial(data).unwrap();
+    assert_eq!(snss, parse(data).unwrap());
+    assert!(rest.is_empty());
+
+    // cut in the last command, then in the length prefix of a command
+    for end in [data.len() - 1, 1639] {
+        let (snss, rest) = parse_partial(&data[..end]).unwrap();
+        assert_eq!(snss.version, 3);
+        assert_eq!(snss.commands.len(), 2);
+        assert_eq!(rest, &data[1638..end]);
+    }
+
+    let mut malformed = snss_bytes(&[(2, &i32s(&[1]))]);
+    malformed.extend([5, 0]);
+    let err = parse_partial(&malformed).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(err.command_index(), Some(0));
+}
+
+#[test]
+fn test_parse_lenient_truncated_last_command() {
+    let data = include_bytes!("Session");
+    // as left by a crash in the middle of writing the last tab
+    let data = &data[..data.len() - 100];
+    assert!(parse(data).is_err());
+
+    let (snss, errors) = parse_lenient(data);
+    let ids: Vec<_> = snss.commands.iter().map(|cmd| cmd.id).collect();
+    assert_eq!(ids, [14, 6]);
+
+    let [err] = errors.try_into().unwrap();
+    assert_eq!(err.kind(), ErrorKind::TruncatedCommand);
+    assert_eq!(err.command_index(), Some(2));
+}
+
+#[test]
+fn test_parse_lenient_bad_header() {
+    let (snss, errors) = parse_lenient(b"SQLite format 3");
+    assert_eq!(snss.version, 0);
+    assert!(snss.commands.is_empty());
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn test_parse_active_window() {
+    let data = snss_bytes(&[(20, &i32s(&[1994883224]))]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    let Content::ActiveWindow { window_id } = cmd.content else {
+        panic!()
+    };
+    assert_eq!(window_id, 1994883224);
+
+    let data = snss_bytes(&[(20, &i32s(&[1994883224, 0]))]);
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.message, "expected a 4 bytes payload");
+}
+
+#[test]
+fn test_page_state() {
+    let data = include_bytes!("Session");
+    let snss = parse(data.as_slice()).unwrap();
+    let tab = snss.tabs().next().unwrap();
+
+    let state = tab.page_state().unwrap();
+    assert_eq!(
+        PageState::parse(&tab.state).unwrap().referrer,
+        state.referrer
+    );
+    assert_eq!(state.version, 33);
+    assert_eq!(state.url.as_deref(), Some(&*tab.url));
+    assert_eq!(
+        state.referrer.as_deref(),
+        Some("https://console.hetzner.cloud/")
+    );
+    assert_eq!(state.target.as_deref(), Some(""));
+    assert_eq!(
+        state.document_state,
+        [
+            "\n\r?% Blink serialized form state version 10 \n\r=&",
+            " [] #0",
+            "1",
+            "",
+            "text",
+            "1",
+            "primary"
+        ]
+    );
+    assert_eq!(
+        state.view_state,
+        Some(ViewState {
+            visual_viewport_scroll_offset: (0.0, 0.0),
+            scroll_offset: (0, 0),
+            page_scale_factor: 1.0,
+        })
+    );
+}
+
+#[test]
+fn test_page_state_scroll_offset() {
+    let data = include_bytes!("Session");
+    let mut data = data.to_vec();
+    // scroll offset of the first tab's view state
+    data[1178..1182].copy_from_slice(&120i32.to_le_bytes());
+    data[1182..1186].copy_from_slice(&3400i32.to_le_bytes());
+
+    let snss = parse(&data).unwrap();
+    let [first, second] = snss.tabs().collect::<Vec<_>>().try_into().unwrap();
+    assert_eq!(
+        first.page_state().unwrap().scroll_offset(),
+        Some((120, 3400))
+    );
+    assert_eq!(second.page_state().unwrap().scroll_offset(), Some((0, 0)));
+}
+
+#[test]
+fn test_page_state_unsupported() {
+    let err = PageState::parse(&i32s(&[8, 25, 0])).unwrap_err();
+    assert_eq!(err.message, "unsupported page state version 25");
+
+    let err = PageState::parse(&[]).unwrap_err();
+    assert_eq!(err.context, ["pickle header"]);
+}
+
+#[test]
+fn test_parse_last_active_time() {
+    let mut payload = i32s(&[7, 0]);
+    payload.extend_from_slice(&13_395_060_000_123_456i64.to_le_bytes());
+    let data = snss_bytes(&[(21, &payload)]);
+
+    let snss = parse(&data).unwrap();
+    let [cmd] = snss.commands.try_into().unwrap();
+
+    let Content::LastActiveTime {
+        tab_id,
+        last_active_time,
+    } = cmd.content
+    else {
+        panic!()
+    };
+    assert_eq!(tab_id, 7);
+    assert_eq!(last_active_time, 13_395_060_000_123_456);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_chrome_time_to_system_time() {
+    // 2025-06-22T10:00:00.123456Z
+    assert_eq!(
+        chrome_time_to_system_time(13_395_060_000_123_456),
+        Some(UNIX_EPOCH + Duration::from_micros(1_750_586_400_123_456))
+    );
+    assert_eq!(
+        chrome_time_to_system_time(11_644_473_600_000_000),
+        Some(UNIX_EPOCH)
+    );
+    assert_eq!(
+        chrome_time_to_system_time(11_644_473_599_000_000),
+        Some(UNIX_EPOCH - Duration::from_secs(1))
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_chrome_time_to_offset_date_time() {
+    let time = chrome_time_to_offset_date_time(13_395_060_000_123_456).unwrap();
+    assert_eq!(time.unix_timestamp(), 1_750_586_400);
+    assert_eq!(time.microsecond(), 123_456);
+    assert_eq!(chrome_time_to_offset_date_time(0).unwrap().year(), 1601);
+    assert_eq!(chrome_time_to_offset_date_time(i64::MAX), None);
+}
+
+#[test]
+fn test_kind() {
+    let data = include_bytes!("Session");
+    assert_eq!(parse(data).unwrap().kind(), FileKind::Session);
+
+    // Window and SelectedNavigationInTab of the tab restore service
+    let data = snss_bytes(&[(3, &[]), (4, &[])]);
+    assert_eq!(parse(&data).unwrap().kind(), FileKind::Tabs);
+
+    let data = snss_bytes(&[]);
+    assert_eq!(parse(&data).unwrap().kind(), FileKind::Unknown);
+
+    let data = snss_bytes(&[(3, &[]), (0, &i32s(&[1, 2]))]);
+    assert_eq!(parse(&data).unwrap().kind(), FileKind::Unknown);
+}
+
+#[test]
+fn test_parse_tab_group() {
+    let token: Vec<u8> = (1..=16).collect();
+    let mut grouped = i32s(&[7, 0]);
+    grouped.extend_from_slice(&token);
+    grouped.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    let mut ungrouped = i32s(&[8, 0]);
+    ungrouped.extend_from_slice(&[0; 24]);
+    let data = snss_bytes(&[(25, &grouped), (25, &ungrouped)]);
+
+    let snss = parse(&data).unwrap();
+    let [grouped, ungrouped] = snss.commands.try_into().unwrap();
+
+    let Content::TabGroup { tab_id, group } = grouped.content else {
+        panic!()
+    };
+    assert_eq!(tab_id, 7);
+    assert_eq!(group.map(Vec::from), Some(token));
+
+    let Content::TabGroup { tab_id, group } = ungrouped.content else {
+        panic!()
+    };
+    assert_eq!(tab_id, 8);
+    assert_eq!(group, None);
+}
+
+#[test]
+fn test_parse_tab_group_metadata() {
+    let token: Vec<u8> = (1..=16).collect();
+    let mut payload = token.clone();
+    payload.extend_from_slice(&4u32.to_le_bytes());
+    payload.extend("Work".encode_utf16().flat_map(u16::to_le_bytes));
+    payload.extend_from_slice(&i32s(&[4, 1]));
+    // saved guid
+    payload.extend_from_slice(&padded_str(""));
+    let data = snss_bytes(&[(27, &pickle(&payload)), (26, &[1, 2, 3])]);
+
+    let snss = parse(&data).unwrap();
+    let [metadata, obsolete] = snss.commands.try_into().unwrap();
+
+    let Content::TabGroupMetadata {
+        group,
+        title,
+        color,
+        collapsed,
+    } = metadata.content
+    else {
+        panic!()
+    };
+    assert_eq!(Vec::from(group), token);
+    assert_eq!(title, "Work");
+    assert_eq!(color, 4);
+    assert!(collapsed);
+
+    assert!(matches!(obsolete.content, Content::Other(_)));
+}
+
+#[test]
+fn test_navigation_history() {
+    let data = snss_bytes(&[
+        (6, &tab_payload(10, 1, "https://a.example/b")),
+        (6, &tab_payload(11, 0, "https://other.example/")),
+        (6, &tab_payload(10, 0, "https://a.example/")),
+        (6, &tab_payload(10, 2, "https://a.example/c")),
+        (6, &tab_payload(10, 1, "https://a.example/b#updated")),
+    ]);
+    let snss = parse(&data).unwrap();
+
+    let urls: Vec<_> = snss
+        .navigation_history(10)
+        .iter()
+        .map(|tab| &*tab.url)
+        .collect();
+    assert_eq!(
+        urls,
+        [
+            "https://a.example/",
+            "https://a.example/b#updated",
+            "https://a.example/c"
+        ]
+    );
+    assert!(snss.navigation_history(12).is_empty());
+}
+
+#[test]
+fn test_windows() {
+    let data = snss_bytes(&[
+        (0, &i32s(&[1, 10])),
+        (0, &i32s(&[1, 11])),
+        (0, &i32s(&[1, 12])),
+        (2, &i32s(&[10, 1])),
+        (2, &i32s(&[11, 0])),
+        (6, &tab_payload(10, 0, "https://a.example/")),
+        (6, &tab_payload(10, 1, "https://a.example/next")),
+        (7, &i32s(&[10, 0])),
+        (6, &tab_payload(11, 0, "https://b.example/")),
+        (6, &tab_payload(12, 0, "https://c.example/")),
+        (8, &i32s(&[1, 1])),
+        (16, &[i32s(&[12, 0]), 0i64.to_le_bytes().to_vec()].concat()),
+    ]);
+    let snss = parse(&data).unwrap();
+
+    let [window] = snss.windows().try_into().unwrap();
+    assert_eq!(window.id, 1);
+    assert_eq!(window.selected_index, Some(1));
+    let urls: Vec<_> = window.tabs.iter().map(|tab| &*tab.url).collect();
+    assert_eq!(urls, ["https://b.example/", "https://a.example/"]);
+}
+
+#[test]
+fn test_windows_two_windows() {
+    let data = snss_bytes(&[
+        (0, &i32s(&[2, 20])),
+        (0, &i32s(&[1, 10])),
+        (0, &i32s(&[1, 11])),
+        (6, &tab_payload(10, 0, "https://a.example/")),
+        (6, &tab_payload(11, 0, "https://b.example/")),
+        (6, &tab_payload(20, 0, "https://c.example/")),
+        (6, &tab_payload(30, 0, "https://orphan.example/")),
+        // the tab is dragged to the second window
+        (0, &i32s(&[2, 11])),
+        (2, &i32s(&[11, 1])),
+        (8, &i32s(&[2, 1])),
+    ]);
+    let snss = parse(&data).unwrap();
+
+    let [second, first] = snss.windows().try_into().unwrap();
+    assert_eq!(second.id, 2);
+    assert_eq!(second.selected_index, Some(1));
+    let urls: Vec<_> = second.tabs.iter().map(|tab| &*tab.url).collect();
+    assert_eq!(urls, ["https://c.example/", "https://b.example/"]);
+    assert_eq!(first.id, 1);
+    assert_eq!(first.selected_index, None);
+    let urls: Vec<_> = first.tabs.iter().map(|tab| &*tab.url).collect();
+    assert_eq!(urls, ["https://a.example/"]);
+}
+
+#[test]
+fn test_parse_tab_guid() {
+    let guid = "123e4567-e89b-12d3-a456-426614174000";
+    let valid = pickle(&[i32s(&[7]), padded_str(guid)].concat());
+    let invalid = pickle(&[i32s(&[8]), padded_str("not a guid")].concat());
+    let data = snss_bytes(&[(28, &valid), (28, &invalid)]);
+
+    let snss = parse(&data).unwrap();
+    let [valid_cmd, invalid_cmd] = snss.commands.try_into().unwrap();
+
+    let Content::TabGuid {
+        tab_id,
+        guid: parsed,
+    } = valid_cmd.content
+    else {
+        panic!()
+    };
+    assert_eq!(tab_id, 7);
+    assert_eq!(parsed, guid);
+
+    let Content::Other(raw) = invalid_cmd.content else {
+        panic!()
+    };
+    assert_eq!(raw, invalid);
+}
+
+#[test]
+fn test_tab_display() {
+    let data = snss_bytes(&[(6, &tab_payload(1, 0, "https://a.example/"))]);
+    let snss = parse(&data).unwrap();
+    let mut tab = snss.into_tabs().next().unwrap();
+    assert_eq!(tab.to_string(), "https://a.example/");
+
+    tab.title = "[draft] notes (v2)".to_string();
+    assert_eq!(
+        tab.to_string(),
+        r"[[draft\] notes (v2\)](https://a.example/)"
+    );
+}
+
+#[test]
+fn test_parse_window_user_title() {
+    // 9 bytes, padded to 12
+    let payload = pickle(&[i32s(&[5]), padded_str("Work 🚀")].concat());
+    let data = snss_bytes(&[(31, &payload), (20, &i32s(&[5]))]);
+
+    let snss = parse(&data).unwrap();
+    let [title, active] = snss.commands.try_into().unwrap();
+
+    let Content::WindowUserTitle { window_id, title } = title.content else {
+        panic!()
+    };
+    assert_eq!(window_id, 5);
+    assert_eq!(title, "Work 🚀");
+    assert!(matches!(
+        active.content,
+        Content::ActiveWindow { window_id: 5 }
+    ));
+}
+
+#[test]
+fn test_page_transition_type_try_from() {
+    assert_eq!(
+        PageTransitionType::try_from(0),
+        Ok(PageTransitionType::Link)
+    );
+    assert_eq!(
+        PageTransitionType::try_from(10),
+        Ok(PageTransitionType::KeywordGenerated)
+    );
+    assert_eq!(PageTransitionType::try_from(11), Err(11));
+    assert_eq!(PageTransition(0x0100000B).kind(), Err(11));
+}
+
+#[test]
+fn test_page_transition_type_display() {
+    assert_eq!(PageTransitionType::Link.to_string(), "link");
+    assert_eq!(
+        PageTransitionType::AutoBookmark.to_string(),
+        "auto_bookmark"
+    );
+    assert_eq!(PageTransitionType::StartPage.to_string(), "auto_toplevel");
+    assert_eq!(
+        PageTransitionType::KeywordGenerated.to_string(),
+        "keyword_generated"
+    );
+}
+
+#[test]
+fn test_page_transition_type_as_str() {
+    for value in 0..=10 {
+        let kind = PageTransitionType::try_from(value).unwrap();
+        assert_eq!(kind.as_str().parse(), Ok(kind));
+    }
+    assert_eq!(PageTransitionType::FormSubmit.as_str(), "form_submit");
+}
+
+#[test]
+fn test_page_transition_type_from_str() {
+    assert_eq!("link".parse(), Ok(PageTransitionType::Link));
+    assert_eq!(
+        "AUTO_BOOKMARK".parse(),
+        Ok(PageTransitionType::AutoBookmark)
+    );
+    assert_eq!("autobookmark".parse(), Ok(PageTransitionType::AutoBookmark));
+    assert_eq!("auto_toplevel".parse(), Ok(PageTransitionType::StartPage));
+    assert_eq!("StartPage".parse(), Ok(PageTransitionType::StartPage));
+
+    let err = "clicked".parse::<PageTransitionType>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown page transition type 'clicked'");
+}
+
+#[test]
+fn test_page_transition_bits() {
+    let transition = PageTransition(0xC0000008);
+    assert_eq!(transition.core_type(), 8);
+    assert_eq!(transition.qualifier_bits(), 0xC0000000);
+
+    let transition = PageTransition(0x0100000B);
+    assert_eq!(transition.core_type(), 11);
+    assert_eq!(transition.qualifier_bits(), 0x01000000);
+}
+
+#[test]
+fn test_page_transition_predicates() {
+    assert!(!PageTransition(0x00000008).is_redirect());
+    assert!(PageTransition(0x40000000).is_redirect());
+    assert!(PageTransition(0x80000001).is_redirect());
+
+    assert!(PageTransition(0x00000000).is_main_frame());
+    assert!(!PageTransition(0x00000003).is_main_frame());
+    assert!(!PageTransition(0x10000004).is_main_frame());
+    assert!(PageTransition(0x0000000B).is_main_frame());
+}
+
+#[test]
+fn test_page_transition_display() {
+    assert_eq!(PageTransition(0).to_string(), "link");
+    assert_eq!(
+        PageTransition(0xC0000008).to_string(),
+        "reload [client_redirect, server_redirect]"
+    );
+    assert_eq!(
+        PageTransition(0x0100000B).to_string(),
+        "Unknown(0xb) [back_forward]"
+    );
+}
+
+#[test]
+fn test_parse_extension_app_id() {
+    let app_id = "mjoklplbddabcmpepnokjaffbmgbkkgg";
+    let valid = pickle(&[i32s(&[3]), padded_str(app_id)].concat());
+    // length past the end of the payload
+    let malformed = pickle(&i32s(&[3, 64]));
+    let data = snss_bytes(&[(13, &valid), (13, &malformed)]);
+
+    let snss = parse(&data).unwrap();
+    let [valid_cmd, malformed_cmd] = snss.commands.try_into().unwrap();
+
+    let Content::ExtensionAppId {
+        tab_id,
+        extension_app_id,
+    } = valid_cmd.content
+    else {
+        panic!()
+    };
+    assert_eq!(tab_id, 3);
+    assert_eq!(extension_app_id, app_id);
+
+    let Content::Other(raw) = malformed_cmd.content else {
+        panic!()
+    };
+    assert_eq!(raw, malformed);
+}
+
+#[test]
+fn test_parse_tabs_extension_app_id() {
+    let app_id = "mjoklplbddabcmpepnokjaffbmgbkkgg";
+    let payload = pickle(&[i32s(&[3]), padded_str(app_id)].concat());
+    let data = snss_bytes(&[
+        (1, &tab_payload(3, 0, "https://example.com/")),
+        (6, &payload),
+    ]);
+
+    let snss = parse_with(&data, FileKind::Tabs).unwrap();
+    assert!(matches!(
+        &snss.commands[1].content,
+        Content::ExtensionAppId { tab_id: 3, extension_app_id } if extension_app_id == app_id
+    ));
+}
+
+#[test]
+fn test_parse_with() {
+    let data = snss_bytes(&[
+        (1, &tab_payload(1, 0, "https://a.example/")),
+        (6, &tab_payload(2, 0, "https://b.example/")),
+    ]);
+
+    let snss = parse_with(&data, FileKind::Tabs).unwrap();
+    let [tab, other] = snss.commands.try_into().unwrap();
+    assert!(matches!(tab.content, Content::Tab(Tab { id: 1, .. })));
+    // kCommandSetExtensionAppID of the tab restore service
+    assert!(!matches!(other.content, Content::Tab(_)));
+
+    let snss = parse_with(&data, FileKind::Session).unwrap();
+    let [other, tab] = snss.commands.try_into().unwrap();
+    assert!(matches!(other.content, Content::Other(_)));
+    assert!(matches!(tab.content, Content::Tab(Tab { id: 2, .. })));
+
+    let snss = parse_with(&data, FileKind::Unknown).unwrap();
+    assert_eq!(snss.tabs().count(), 2);
+}
+
+#[test]
+fn test_parse_detects_tabs_file() {
+    // RestoredEntry of the tab restore service would be a truncated
+    // kCommandSetTabIndexInWindow in a Session file
+    let data = snss_bytes(&[
+        (1, &tab_payload(1, 0, "https://a.example/")),
+        (2, &i32s(&[1])),
+    ]);
+
+    let snss = parse(&data).unwrap();
+    assert_eq!(snss.kind(), FileKind::Tabs);
+    assert_eq!(snss.tabs().count(), 1);
+}
+
+#[test]
+fn test_parse_detects_tabs_file_with_high_ids() {
+    // kCommandSetWindowUserTitle of the tab restore service
+    let data = snss_bytes(&[
+        (1, &tab_payload(1, 0, "https://a.example/")),
+        (6, &[]),
+        (12, &[]),
+    ]);
+
+    let snss = parse(&data).unwrap();
+    assert_eq!(snss.kind(), FileKind::Tabs);
+    assert_eq!(snss.tabs().count(), 1);
+}
+
+#[cfg(feature = "bitflags")]
+#[test]
+fn test_qualifier_flags() {
+    let flags = PageTransition(0xC6000000).qualifier_flags();
+    assert_eq!(
+        flags,
+        PageTransitionQualifierFlags::ADDRESS_BAR
+            | PageTransitionQualifierFlags::HOMEPAGE
+            | PageTransitionQualifierFlags::CLIENT_REDIRECT
+            | PageTransitionQualifierFlags::SERVER_REDIRECT
+    );
+    assert!(flags.intersects(
+        PageTransitionQualifierFlags::CLIENT_REDIRECT
+            | PageTransitionQualifierFlags::SERVER_REDIRECT
+    ));
+    assert!(
+        !PageTransition(0x01000008)
+            .qualifier_flags()
+            .intersects(PageTransitionQualifierFlags::CLIENT_REDIRECT)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_file_kind_from_file_name() {
+    assert_eq!(
+        FileKind::from_file_name("Session_13395060000123456"),
+        FileKind::Session
+    );
+    assert_eq!(
+        FileKind::from_file_name("Current Session"),
+        FileKind::Session
+    );
+    assert_eq!(
+        FileKind::from_file_name("Tabs_13395060000123456"),
+        FileKind::Tabs
+    );
+    assert_eq!(FileKind::from_file_name("Last Tabs"), FileKind::Tabs);
+    assert_eq!(FileKind::from_file_name("Session"), FileKind::Unknown);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parse_file_named_session() {
+    let dir = std::env::temp_dir().join("snss-test-parse-file-named-session");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Session_13395060000123456");
+    // would be detected as a Tabs file from its content
+    let data = snss_bytes(&[(1, &tab_payload(1, 0, "https://a.example/"))]);
+    std::fs::write(&path, data).unwrap();
+
+    let snss = parse_file(&path).unwrap();
+    assert_eq!(snss.tabs().count(), 0);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parse_dir() {
+    let dir = std::env::temp_dir().join("snss-test-parse-dir");
+    std::fs::create_dir_all(&dir).unwrap();
+    let data = include_bytes!("Session");
+    std::fs::write(dir.join("Session_13395060000123456"), data).unwrap();
+    std::fs::write(dir.join("Tabs_13395060000123456"), &data[..data.len() - 1]).unwrap();
+    std::fs::write(dir.join("Preferences"), "{}").unwrap();
+    std::fs::create_dir_all(dir.join("Sessions")).unwrap();
+
+    let results = parse_dir(&dir);
+    let [(session_path, session), (tabs_path, tabs)] = results.try_into().unwrap();
+    assert_eq!(session_path, dir.join("Session_13395060000123456"));
+    assert_eq!(session.unwrap().commands.len(), 3);
+    assert_eq!(tabs_path, dir.join("Tabs_13395060000123456"));
+    assert_eq!(tabs.unwrap_err().kind(), ErrorKind::TruncatedCommand);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    let [(path, err)] = parse_dir(&dir).try_into().unwrap();
+    assert_eq!(path, dir);
+    assert!(err.unwrap_err().message().starts_with("failed to read"));
+}
+
+#[test]
+fn test_detect_kind() {
+    let data = include_bytes!("Session");
+    assert_eq!(parse(data).unwrap().detect_kind(), Some(FileKind::Session));
+
+    let data = snss_bytes(&[
+        (1, &tab_payload(1, 0, "https://a.example/")),
+        (4, &i32s(&[1, 0])),
+    ]);
+    assert_eq!(parse(&data).unwrap().detect_kind(), Some(FileKind::Tabs));
+
+    // SetTabWindow of the session service next to a Window of the tab restore service
+    let data = snss_bytes(&[(3, &[]), (0, &i32s(&[1, 2]))]);
+    assert_eq!(parse(&data).unwrap().detect_kind(), None);
+    assert_eq!(parse(&snss_bytes(&[])).unwrap().detect_kind(), None);
+}
+
+#[test]
+fn test_probe() {
+    let data = include_bytes!("Session");
+    assert_eq!(probe(data).unwrap(), (3, 3));
+
+    assert_eq!(probe(&snss_bytes(&[])).unwrap(), (3, 0));
+
+    let err = probe(&data[..data.len() - 1]).unwrap_err();
+    assert_eq!(err.context, ["command"]);
+}
+
+#[test]
+fn test_snss_reader() {
+    let data = include_bytes!("Session");
+    let mut reader = SnssReader::new(data).unwrap();
+    assert_eq!(reader.version(), 3);
+
+    let first = reader.next().unwrap().unwrap();
+    assert_eq!(first.id, 14);
+    assert_eq!(reader.count(), 2);
+
+    let data = snss_bytes(&[(2, &i32s(&[1])), (20, &i32s(&[1]))]);
+    let mut reader = SnssReader::new(&data).unwrap();
+    let err = reader.next().unwrap().unwrap_err();
+    assert_eq!(err.context, ["index"]);
+    assert!(reader.next().is_none());
+
+    let err = SnssReader::new(b"SNSX").unwrap_err();
+    assert_eq!(err.offset, 0);
+}
+
+#[test]
+fn test_pinned_tabs() {
+    let data = snss_bytes(&[
+        (12, &i32s(&[5, 1])),
+        (12, &i32s(&[3, 1])),
+        (12, &i32s(&[4, 1])),
+        (12, &i32s(&[4, 0])),
+    ]);
+    let snss = parse(&data).unwrap();
+
+    assert_eq!(snss.pinned_tabs(), [3, 5]);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json() {
+    let data = include_bytes!("Session");
+    let json = parse(data).unwrap().to_json();
+
+    assert_eq!(
+        json["commands"][1]["content"]["Tab"]["transition"],
+        serde_json::json!({
+            "raw": 8,
+            "kind": "Reload",
+            "back_forward": false,
+            "address_bar": false,
+            "homepage": false,
+            "chain_start": false,
+            "redirect_chain_end": false,
+            "client_redirect": false,
+            "server_redirect": false,
+        })
+    );
+    assert_eq!(
+        json["commands"][0]["content"]["WindowBounds"]["width"],
+        1524
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_sessions_json() {
+    let data = snss_bytes(&[
+        (0, &i32s(&[1, 10])),
+        (0, &i32s(&[1, 11])),
+        (2, &i32s(&[11, 1])),
+        (6, &tab_payload(10, 0, "https://a.example/")),
+        (6, &tab_payload(11, 0, "https://b.example/")),
+        (12, &i32s(&[10, 1])),
+        (8, &i32s(&[1, 0])),
+    ]);
+    let json = parse(&data).unwrap().to_sessions_json();
+
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "windows": [{
+                "id": 1,
+                "selected_index": 0,
+                "tabs": [
+                    {
+                        "id": 10,
+                        "url": "https://a.example/",
+                        "title": "",
+                        "transition": "link",
+                        "pinned": true,
+                    },
+                    {
+                        "id": 11,
+                        "url": "https://b.example/",
+                        "title": "",
+                        "transition": "link",
+                        "pinned": false,
+                    },
+                ],
+            }],
+        })
+    );
+}
+
+#[test]
+fn test_tab_groups() {
+    let group = |tab_id, token: Option<u8>| {
+        let mut payload = i32s(&[tab_id, 0]);
+        payload.extend_from_slice(&[token.unwrap_or(0); 16]);
+        payload.extend_from_slice(&[token.is_some() as u8, 0, 0, 0, 0, 0, 0, 0]);
+        payload
+    };
+    let data = snss_bytes(&[
+        (25, &group(1, Some(0xAA))),
+        (25, &group(2, Some(0xBB))),
+        (25, &group(3, Some(0xAA))),
+        (25, &group(2, Some(0xAA))),
+        (25, &group(3, None)),
+    ]);
+    let snss = parse(&data).unwrap();
+
+    let groups = snss.tab_groups();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[&[0xAA; 16]], [1, 2]);
+}
+
+#[test]
+fn test_commands_iter() {
+    let data = include_bytes!("Session");
+    let commands: Vec<_> = commands_iter(data).collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        format!("{commands:?}"),
+        format!("{:?}", parse(data).unwrap().commands)
+    );
+
+    let mut iter = commands_iter(&data[..4]);
+    assert_eq!(
+        iter.next().unwrap().unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_command_payload_len() {
+    let data = include_bytes!("Session");
+    let snss = parse(data).unwrap();
+
+    let lens: Vec<_> = snss
+        .commands
+        .iter()
+        .map(|command| command.payload_len)
+        .collect();
+    assert_eq!(lens, [24, 1600, 1624]);
+    for command in &snss.commands {
+        let prefix = &data[command.offset - 2..command.offset];
+        assert_eq!(
+            u16::from_le_bytes(prefix.try_into().unwrap()),
+            command.payload_len + 1
+        );
+    }
+}
+
+#[test]
+fn test_command_payload_range() {
+    let data = include_bytes!("Session");
+    let snss = parse(data).unwrap();
+
+    let ranges: Vec<_> = snss.commands.iter().map(Command::payload_range).collect();
+    assert_eq!(ranges, [11..35, 38..1638, 1641..3265]);
+
+    let payload = [1, 2, 3];
+    let data = snss_bytes(&[(20, &i32s(&[5])), (99, &payload)]);
+    let snss = parse(&data).unwrap();
+    assert_eq!(data[snss.commands[1].payload_range()], payload);
+}
+
+#[test]
+fn test_command_offset() {
+    let data = include_bytes!("Session");
+    let snss = parse(data).unwrap();
+
+    let offsets: Vec<_> = snss.commands.iter().map(|command| command.offset).collect();
+    assert_eq!(offsets[..2], [10, 37]);
+    for command in &snss.commands {
+        assert_eq!(data[command.offset], command.id);
+    }
+
+    let data = snss_bytes(&[(2, &i32s(&[1])), (20, &i32s(&[1]))]);
+    let (snss, _) = parse_lenient(&data);
+    assert_eq!(snss.commands[0].offset, 17);
+}
+
+#[test]
+fn test_error_command_index() {
+    let original = include_bytes!("Session");
+    // cut the first tab in the middle of its url, which starts at 0x36
+    let mut data = original[..35].to_vec();
+    data.extend_from_slice(&27u16.to_le_bytes());
+    data.extend_from_slice(&original[37..37 + 27]);
+    data.extend_from_slice(&original[1638..]);
+
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.command_index(), Some(1));
+    assert_eq!(err.offset(), 0x36);
+    #[cfg(feature = "std")]
+    assert_eq!(err.message(), "length 70 exceeds remaining 10 bytes");
+    assert_eq!(err.context(), ["url"]);
+
+    let (snss, errors) = parse_lenient(&data);
+    assert_eq!(snss.commands.len(), 2);
+    assert_eq!(errors[0].command_index(), Some(1));
+
+    assert_eq!(parse(&data[..4]).unwrap_err().command_index(), None);
+}
+
+#[test]
+fn test_parse_lossy() {
+    let data = include_bytes!("Session");
+    let mut data = data.to_vec();
+    // invalid UTF-8 as the first byte of the first tab's url
+    data[0x36] = 0xFF;
+    assert!(parse(&data).is_err());
+
+    let options = ParseOptions {
+        lossy: true,
+        ..ParseOptions::default()
+    };
+    let snss = parse_with_options(&data, options).unwrap();
+    let [first, second] = snss.tabs().collect::<Vec<_>>().try_into().unwrap();
+    assert_eq!(
+        first.url,
+        "\u{FFFD}ttps://console.hetzner.cloud/projects/3687808/servers/64199561/graphs"
+    );
+    assert!(matches!(first.url, Cow::Owned(_)));
+    assert!(matches!(second.url, Cow::Borrowed(_)));
+}
+
+#[test]
+fn test_parse_limits() {
+    let data = include_bytes!("Session");
+
+    let options = ParseOptions {
+        max_commands: Some(2),
+        ..ParseOptions::default()
+    };
+    let err = parse_with_options(data, options).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    assert_eq!(err.message(), "more than 2 commands");
+    assert_eq!(err.command_index(), Some(2));
+    assert_eq!(err.offset(), 1638);
+
+    let options = ParseOptions {
+        max_payload_len: Some(1024),
+        ..ParseOptions::default()
+    };
+    let err = parse_with_options(data, options).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    assert_eq!(
+        err.message(),
+        "command payload of 1600 bytes exceeds 1024 bytes"
+    );
+    assert_eq!(err.command_index(), Some(1));
+    assert_eq!(err.offset(), 35);
+
+    let options = ParseOptions {
+        max_commands: Some(3),
+        max_payload_len: Some(1624),
+        ..ParseOptions::default()
+    };
+    assert_eq!(parse_with_options(data, options).unwrap().commands.len(), 3);
+}
+
+#[test]
+fn test_tab_transition_helpers() {
+    let data = include_bytes!("Session");
+    let snss = parse(data).unwrap();
+    let mut tab = snss.into_tabs().next().unwrap();
+    assert!(tab.is_reload());
+    assert!(!tab.is_form_submit());
+    assert!(!tab.came_from_redirect());
+
+    tab.transition = PageTransition(0x80000007);
+    assert!(!tab.is_reload());
+    assert!(tab.is_form_submit());
+    assert!(tab.came_from_redirect());
+}
+
+#[test]
+fn test_error_bad_magic() {
+    for data in [b"XXXX\x03\0\0\0".as_slice(), b""] {
+        let err = parse(data).unwrap_err();
+        assert_eq!(err.offset(), 0);
+        assert_eq!(err.message(), "not an SNSS file: expected magic 'SNSS'");
+    }
+}
+
+#[test]
+fn test_error_kind() {
+    let data = include_bytes!("Session");
+
+    let err = parse(b"SQLite format 3").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BadMagic);
+
+    let err = parse(&data[..data.len() - 1]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TruncatedCommand);
+
+    let mut invalid = data.to_vec();
+    invalid[0x36] = 0xFF;
+    let err = parse(&invalid).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidUtf8 { field: "url" });
+
+    let data = snss_bytes(&[(2, &i32s(&[1]))]);
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(
+        err.to_string(),
+        "error at offset 15 in field 'index': invalid index\n"
+    );
+}
+
+#[test]
+fn test_unsupported_version() {
+    let mut data = include_bytes!("Session").to_vec();
+    // encrypted
+    data[4] = 2;
+
+    let err = parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnsupportedVersion(2));
+    assert_eq!(err.offset(), 4);
+    assert_eq!(err.message(), "unsupported SNSS version 2");
+
+    let (snss, errors) = parse_lenient(&data);
+    assert_eq!(snss.version, 2);
+    assert!(snss.commands.is_empty());
+    assert_eq!(errors.len(), 1);
+
+    assert_eq!(probe(&data).unwrap(), (2, 3));
+
+    data[4] = 1;
+    assert_eq!(parse(&data).unwrap().version, 1);
+}
+
+#[test]
+fn test_to_bytes() {
+    let data = include_bytes!("Session");
+    let snss = parse(data).unwrap();
+
+    assert_eq!(snss.to_bytes().unwrap(), data);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_eq_hash() {
+    let data = include_bytes!("Session");
+    let snss = parse(data).unwrap();
+
+    assert_eq!(parse(&snss.to_bytes().unwrap()).unwrap(), snss);
+    assert_ne!(snss.commands[0], snss.commands[1]);
+
+    let tabs: std::collections::HashSet<_> = snss.tabs().chain(snss.tabs()).collect();
+    assert_eq!(tabs.len(), 2);
+}
+
+#[test]
+fn test_to_bytes_other() {
+    let data = snss_bytes(&[
+        (99, &[1, 2, 3]),
+        (6, &tab_payload(1, 0, "https://a.example/")),
+        (98, &[]),
+        // malformed window bounds
+        (14, &i32s(&[1, 2, 3, 4, 5])),
+    ]);
+    let snss = parse(&data).unwrap();
+    let others = |snss: &SNSS| -> Vec<_> {
+        snss.commands
+            .iter()
+            .filter_map(|cmd| match &cmd.content {
+                Content::Other(payload) => Some((cmd.id, cmd.payload_len, payload.clone())),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let bytes = snss.to_bytes().unwrap();
+    assert_eq!(bytes, data);
+    let expected = [
+        (99, 3, vec![1, 2, 3]),
+        (98, 0, vec![]),
+        (14, 20, i32s(&[1, 2, 3, 4, 5])),
+    ];
+    assert_eq!(others(&parse(&bytes).unwrap()), expected);
+}
+
+#[test]
+fn test_to_bytes_built() {
+    let tab = |id, url: &'static str| {
+        let tab = TabBuilder::new()
+            .id(id)
+            .url(url)
+            .title("Example")
+            .transition(PageTransition(0x01000000))
+            .build();
+        Command::new(6, Content::Tab(tab))
+    };
+    let mut snss = SNSS::new(3);
+    snss.push(tab(1, "https://a.example/"))
+        .push(tab(2, "https://b.example/"))
+        .push(Command::new(20, Content::ActiveWindow { window_id: 1 }));
+
+    let bytes = snss.to_bytes().unwrap();
+    let parsed = parse(&bytes).unwrap();
+    let tabs: Vec<_> = parsed.tabs().map(ToString::to_string).collect();
+    assert_eq!(
+        tabs,
+        [
+            "[Example](https://a.example/)",
+            "[Example](https://b.example/)"
+        ]
+    );
+    assert!(
+        parsed
+            .tabs()
+            .all(|tab| tab.transition.qualifiers().back_forward)
+    );
+    assert!(matches!(
+        parsed.commands[2].content,
+        Content::ActiveWindow { window_id: 1 }
+    ));
+}
+
+#[test]
+fn test_to_bytes_commands() {
+    let token: Vec<u8> = (1..=16).collect();
+    let mut group = i32s(&[7, 0]);
+    group.extend_from_slice(&token);
+    group.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    let mut closed = i32s(&[7, 0]);
+    closed.extend_from_slice(&13_395_060_000_123_456i64.to_le_bytes());
+    let data = snss_bytes(&[
+        (0, &i32s(&[1, 2])),
+        (11, &i32s(&[7, 3])),
+        (12, &i32s(&[3, 1])),
+        (15, &pickle(&[i32s(&[1]), padded_str("Café")].concat())),
+        (16, &closed),
+        (20, &i32s(&[5])),
+        (24, &i32s(&[7, 2, 3])),
+        (25, &group),
+        (23, &pickle(&[i32s(&[5]), padded_str("desk-2")].concat())),
+        (31, &pickle(&[i32s(&[5]), padded_str("Work 🚀")].concat())),
+        (99, &[1, 2, 3]),
+    ]);
+
+    assert_eq!(parse(&data).unwrap().to_bytes().unwrap(), data);
+}
+
+#[test]
+fn test_to_bytes_too_large() {
+    let data = include_bytes!("Session");
+    let mut snss = parse(data).unwrap();
+    snss.commands[1].content = Content::Other(vec![0; 65535]);
+
+    let err = snss.to_bytes().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    assert_eq!(err.command_index(), Some(1));
+    // after the header and the first command
+    assert_eq!(err.offset(), 8 + 2 + 25);
+}
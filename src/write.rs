@@ -0,0 +1,270 @@
+//! Encoding of a [SNSS] back to the bytes Chromium reads, see [SNSS::to_bytes]
+
+use alloc::{format, vec, vec::Vec};
+
+use crate::{Command, Content, Error, ErrorKind, SNSS, Tab};
+
+impl SNSS<'_> {
+    /// Encode the file back to bytes
+    ///
+    /// Parsing the result gives back the same commands. Commands kept as [Content::Other] are
+    /// written as is and tabs keep their undecoded fields (see [Tab::extra]), while other commands
+    /// leave out the trailing bytes they don't decode (eg. the saved GUID of
+    /// [Content::TabGroupMetadata]). Padding is zeroed, as Chromium does.
+    ///
+    /// Fails with [ErrorKind::LimitExceeded] if the payload of a command doesn't fit in its 16 bits
+    /// length prefix, eg. a tab parsed without its trailing fields (see [Tab]) that gets them back.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = b"SNSS".to_vec();
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        for (index, command) in self.commands.iter().enumerate() {
+            command.write(&mut bytes).map_err(|err| Error {
+                command_index: Some(index),
+                ..err
+            })?;
+        }
+        Ok(bytes)
+    }
+}
+
+impl Command<'_> {
+    /// Write the command with its original id, after a length prefix recomputed from the encoded
+    /// payload (rather than [Command::payload_len]) and counting the id
+    fn write(&self, bytes: &mut Vec<u8>) -> Result<(), Error> {
+        let payload = self.content.to_payload();
+        let len = u16::try_from(payload.len() + 1).map_err(|_| Error {
+            kind: ErrorKind::LimitExceeded,
+            message: format!(
+                "command payload of {} bytes exceeds {} bytes",
+                payload.len(),
+                u16::MAX - 1
+            ),
+            offset: bytes.len(),
+            context: vec!["command"],
+            command_index: None,
+        })?;
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.push(self.id);
+        bytes.extend_from_slice(&payload);
+        Ok(())
+    }
+}
+
+impl Content<'_> {
+    fn to_payload(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        match self {
+            Content::Tab(tab) => return pickle(|w| tab.write(w)),
+            Content::TabWindow { window_id, tab_id } => {
+                w.i32(*window_id);
+                w.i32(*tab_id);
+            }
+            Content::TabIndexInWindow { tab_id, index }
+            | Content::SelectedNavigationIndex { tab_id, index } => {
+                w.i32(*tab_id);
+                w.i32(*index);
+            }
+            Content::SelectedTabInIndex { window_id, index } => {
+                w.i32(*window_id);
+                w.i32(*index);
+            }
+            Content::WindowType {
+                window_id,
+                window_type,
+            } => {
+                w.i32(*window_id);
+                w.i32(*window_type);
+            }
+            Content::PinnedState { tab_id, pinned } => {
+                w.i32(*tab_id);
+                w.bool(*pinned);
+            }
+            Content::ExtensionAppId {
+                tab_id,
+                extension_app_id,
+            } => {
+                return pickle(|w| {
+                    w.i32(*tab_id);
+                    w.str(extension_app_id);
+                });
+            }
+            Content::WindowBounds {
+                window_id,
+                x,
+                y,
+                width,
+                height,
+                show_state,
+            } => {
+                for value in [window_id, x, y, width, height, show_state] {
+                    w.i32(*value);
+                }
+            }
+            Content::WindowAppName {
+                window_id,
+                app_name: name,
+            }
+            | Content::WindowWorkspace {
+                window_id,
+                workspace: name,
+            }
+            | Content::WindowUserTitle {
+                window_id,
+                title: name,
+            } => {
+                return pickle(|w| {
+                    w.i32(*window_id);
+                    w.str(name);
+                });
+            }
+            Content::TabClosed {
+                tab_id: id,
+                close_time: time,
+            }
+            | Content::WindowClosed {
+                window_id: id,
+                close_time: time,
+            }
+            | Content::LastActiveTime {
+                tab_id: id,
+                last_active_time: time,
+            } => {
+                w.i32(*id);
+                // padding for the 8-bytes alignment of the timestamp
+                w.i32(0);
+                w.0.extend_from_slice(&time.to_le_bytes());
+            }
+            Content::TabUserAgentOverride {
+                tab_id,
+                user_agent,
+                client_hints,
+            } => {
+                return pickle(|w| {
+                    w.i32(*tab_id);
+                    w.str(user_agent);
+                    w.0.extend_from_slice(client_hints.as_deref().unwrap_or_default());
+                });
+            }
+            Content::ActiveWindow { window_id } => w.i32(*window_id),
+            Content::NavigationPathPruned { tab_id, count, .. } => {
+                w.i32(*tab_id);
+                w.i32(*count);
+            }
+            Content::TabNavigationPathPruned {
+                tab_id,
+                index,
+                count,
+            } => {
+                w.i32(*tab_id);
+                w.i32(*index);
+                w.i32(*count);
+            }
+            Content::TabGroup { tab_id, group } => {
+                w.i32(*tab_id);
+                // padding for the 8-bytes alignment of the token
+                w.i32(0);
+                w.0.extend_from_slice(&group.unwrap_or_default());
+                w.0.push(group.is_some() as u8);
+                // struct padding
+                w.0.extend_from_slice(&[0; 7]);
+            }
+            Content::TabGroupMetadata {
+                group,
+                title,
+                color,
+                collapsed,
+            } => {
+                return pickle(|w| {
+                    w.0.extend_from_slice(group);
+                    w.str16(title);
+                    w.i32(*color);
+                    w.bool(*collapsed);
+                });
+            }
+            Content::TabGuid { tab_id, guid } => {
+                return pickle(|w| {
+                    w.i32(*tab_id);
+                    w.str(guid);
+                });
+            }
+            Content::WindowVisibleOnAllWorkspaces {
+                window_id,
+                visible_on_all_workspaces,
+            } => {
+                w.i32(*window_id);
+                w.bool(*visible_on_all_workspaces);
+            }
+            Content::Other(payload) => return payload.clone(),
+        }
+        w.0
+    }
+}
+
+impl Tab<'_> {
+    fn write(&self, w: &mut Writer) {
+        w.i32(self.id);
+        w.i32(self.index);
+        w.str(&self.url);
+        w.str16(&self.title);
+        w.bytes(&self.state);
+        w.u32(self.transition.0);
+        w.bool(self.post);
+        w.str(&self.referrer_url);
+        w.i32(self.reference_policy);
+        w.str(&self.original_request_url);
+        w.bool(self.user_agent);
+        w.0.extend_from_slice(&self.extra);
+    }
+}
+
+/// `base::Pickle` of what `write` writes, prefixed by its size
+fn pickle(write: impl FnOnce(&mut Writer)) -> Vec<u8> {
+    let mut w = Writer::default();
+    write(&mut w);
+    let mut bytes = (w.0.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(&w.0);
+    bytes
+}
+
+/// Little-endian fields, with strings padded to a 4-bytes boundary
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn i32(&mut self, value: i32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.i32(value as i32);
+    }
+
+    /// Length-prefixed bytes
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.padded(bytes);
+    }
+
+    /// Length-prefixed UTF-8 string
+    fn str(&mut self, string: &str) {
+        self.bytes(string.as_bytes());
+    }
+
+    /// Length-prefixed (in code units) UTF-16 string
+    fn str16(&mut self, string: &str) {
+        let units: Vec<u16> = string.encode_utf16().collect();
+        self.u32(units.len() as u32);
+        let bytes: Vec<u8> = units.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+        self.padded(&bytes);
+    }
+
+    fn padded(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+        let padding = bytes.len().next_multiple_of(4) - bytes.len();
+        self.0.extend_from_slice(&[0; 3][..padding]);
+    }
+}
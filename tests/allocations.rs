@@ -0,0 +1,50 @@
+//! Allocations of [snss::parse] against [snss::parse_owned] on a multi-megabyte file
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    let count = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    drop(result);
+    count
+}
+
+#[test]
+fn test_borrowed_strings_allocate_less() {
+    // the commands of the sample Session repeated past 4 MB
+    let sample = include_bytes!("../src/tests/Session");
+    let mut data = sample.to_vec();
+    while data.len() < 4 << 20 {
+        data.extend_from_slice(&sample[8..]);
+    }
+
+    let borrowed = allocations(|| snss::parse(&data).unwrap());
+    let owned = allocations(|| snss::parse_owned(&data).unwrap());
+    println!(
+        "{} bytes: parse {borrowed} allocations, parse_owned {owned}",
+        data.len()
+    );
+    assert!(borrowed < owned, "{borrowed} >= {owned}");
+}